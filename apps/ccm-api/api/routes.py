@@ -1,5 +1,5 @@
 from fastapi import APIRouter, HTTPException, Depends, BackgroundTasks
-from typing import List, Optional, Dict, Any
+from typing import List, Optional, Dict, Any, Tuple
 from datetime import datetime, timedelta
 import asyncpg
 import redis.asyncio as redis
@@ -33,6 +33,63 @@ async def get_redis():
     # This will be injected from main.py
     pass
 
+# What the database or the timestamp parser raises for a malformed event, as
+# opposed to a server fault; answered with 422 so clients drop the event
+# instead of retrying it
+EVENT_DATA_ERRORS = (ValueError, asyncpg.DataError, asyncpg.IntegrityConstraintViolationError)
+
+async def store_context_event(event: ContextEvent, db: asyncpg.Connection) -> Tuple[str, bool]:
+    """Insert one context event and return its id, and whether it is new.
+    An event whose eventId is already stored is a client retry and is skipped."""
+    event_id = str(event.eventId or uuid.uuid4())
+    inserted = await db.fetchval("""
+        INSERT INTO context_events (id, session_id, agent, type, payload, timestamp)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (id) DO NOTHING
+        RETURNING id
+    """, event_id, event.sessionId, event.agent.value, event.type.value,
+        json.dumps(event.payload), datetime.fromisoformat(event.timestamp.replace('Z', '+00:00')))
+    return event_id, inserted is not None
+
+async def cache_context_event(event: ContextEvent, event_id: str, redis_conn: redis.Redis):
+    """Keep the last 100 events of the session in Redis for a day"""
+    cache_key = f"session:{event.sessionId}:recent"
+    event_data = {
+        "id": event_id,
+        "agent": event.agent.value,
+        "type": event.type.value,
+        "payload": event.payload,
+        "timestamp": event.timestamp
+    }
+
+    await redis_conn.lpush(cache_key, json.dumps(event_data))
+    await redis_conn.ltrim(cache_key, 0, 99)
+    await redis_conn.expire(cache_key, 86400)
+
+async def fetch_recent_events(db: asyncpg.Connection, session_id: str) -> List[ContextEvent]:
+    """Context events of the last 30 minutes, newest first"""
+    since_time = datetime.utcnow() - timedelta(minutes=30)
+
+    query = """
+        SELECT session_id, agent, type, payload, timestamp
+        FROM context_events
+        WHERE session_id = $1 AND timestamp > $2
+        ORDER BY timestamp DESC
+    """
+
+    rows = await db.fetch(query, session_id, since_time)
+
+    events = []
+    for row in rows:
+        events.append(ContextEvent(
+            sessionId=row["session_id"],
+            agent=Agent(row["agent"]),
+            type=EventType(row["type"]),
+            payload=row["payload"],
+            timestamp=row["timestamp"].isoformat()
+        ))
+    return events
+
 @router.post("/context/events")
 async def create_context_event(
     event: ContextEvent,
@@ -43,36 +100,62 @@ async def create_context_event(
     """Store a context event and trigger analysis"""
     try:
         # Store in PostgreSQL (same as before)
-        event_id = str(uuid.uuid4())
-        await db.execute("""
-            INSERT INTO context_events (id, session_id, agent, type, payload, timestamp)
-            VALUES ($1, $2, $3, $4, $5, $6)
-        """, event_id, event.sessionId, event.agent.value, event.type.value,
-            json.dumps(event.payload), datetime.fromisoformat(event.timestamp.replace('Z', '+00:00')))
-
-        # Cache in Redis
-        cache_key = f"session:{event.sessionId}:recent"
-        event_data = {
-            "id": event_id,
-            "agent": event.agent.value,
-            "type": event.type.value,
-            "payload": event.payload,
-            "timestamp": event.timestamp
-        }
+        event_id, inserted = await store_context_event(event, db)
 
-        await redis_conn.lpush(cache_key, json.dumps(event_data))
-        await redis_conn.ltrim(cache_key, 0, 99)
-        await redis_conn.expire(cache_key, 86400)
+        if inserted:
+            # Cache in Redis
+            await cache_context_event(event, event_id, redis_conn)
 
-        # Background analysis
-        background_tasks.add_task(analyze_event_context, event.sessionId, event_id, db, redis_conn)
+            # Background analysis
+            background_tasks.add_task(analyze_event_context, event.sessionId, event_id, db, redis_conn)
 
         return {"status": "success", "event_id": event_id}
 
+    except EVENT_DATA_ERRORS as e:
+        raise HTTPException(status_code=422, detail=f"Invalid event: {str(e)}")
     except Exception as e:
         print(f"Error storing context event: {e}")
         raise HTTPException(status_code=500, detail=f"Failed to store event: {str(e)}")
 
+@router.post("/context/events/batch")
+async def create_context_events(
+    events: List[ContextEvent],
+    background_tasks: BackgroundTasks,
+    db: asyncpg.Connection = Depends(get_db),
+    redis_conn: redis.Redis = Depends(get_redis)
+):
+    """Store several context events at once, e.g. the desktop offline queue.
+    The batch is stored in one transaction, so it is accepted or rejected as a whole;
+    a malformed event rejects it with 422, naming the event's index."""
+    try:
+        stored = []
+        async with db.transaction():
+            for index, event in enumerate(events):
+                try:
+                    stored.append(await store_context_event(event, db))
+                except EVENT_DATA_ERRORS as e:
+                    raise HTTPException(status_code=422, detail=f"Invalid event at index {index}: {str(e)}")
+        event_ids = [event_id for event_id, _ in stored]
+
+        latest = {}
+        for event, (event_id, inserted) in zip(events, stored):
+            if not inserted:
+                continue
+            await cache_context_event(event, event_id, redis_conn)
+            latest[event.sessionId] = event_id
+
+        # One analysis per session, from its newest event
+        for session_id, event_id in latest.items():
+            background_tasks.add_task(analyze_event_context, session_id, event_id, db, redis_conn)
+
+        return {"status": "success", "event_ids": event_ids}
+
+    except HTTPException:
+        raise
+    except Exception as e:
+        print(f"Error storing context events: {e}")
+        raise HTTPException(status_code=500, detail=f"Failed to store events: {str(e)}")
+
 @router.post("/ai/prompt")
 async def generate_ai_prompt(
     request: AIPromptRequest,
@@ -213,6 +296,20 @@ async def suggest_branch(
         print(f"Error suggesting branch: {e}")
         raise HTTPException(status_code=500, detail=f"Branch suggestion failed: {str(e)}")
 
+@router.get("/flow/state/{session_id}")
+async def get_flow_state(
+    session_id: str,
+    db: asyncpg.Connection = Depends(get_db)
+):
+    """Get current flow state only, without insights or break suggestions"""
+    try:
+        events = await fetch_recent_events(db, session_id)
+        return await flow_service.detect_flow_state(events, session_id)
+
+    except Exception as e:
+        print(f"Error getting flow state: {e}")
+        raise HTTPException(status_code=500, detail=f"Flow state failed: {str(e)}")
+
 @router.get("/flow/status/{session_id}")
 async def get_flow_status(
     session_id: str,
@@ -220,27 +317,7 @@ async def get_flow_status(
 ):
     """Get current flow status"""
     try:
-        # Get recent events
-        since_time = datetime.utcnow() - timedelta(minutes=30)
-
-        query = """
-            SELECT session_id, agent, type, payload, timestamp
-            FROM context_events
-            WHERE session_id = $1 AND timestamp > $2
-            ORDER BY timestamp DESC
-        """
-
-        rows = await db.fetch(query, session_id, since_time)
-
-        events = []
-        for row in rows:
-            events.append(ContextEvent(
-                sessionId=row["session_id"],
-                agent=Agent(row["agent"]),
-                type=EventType(row["type"]),
-                payload=row["payload"],
-                timestamp=row["timestamp"].isoformat()
-            ))
+        events = await fetch_recent_events(db, session_id)
 
         flow_state = await flow_service.detect_flow_state(events, session_id)
         flow_insights = await flow_service.get_flow_insights(session_id)
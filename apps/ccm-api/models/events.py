@@ -2,6 +2,7 @@ from pydantic import BaseModel, Field
 from typing import Dict, Any, Optional, List
 from datetime import datetime
 from enum import Enum
+from uuid import UUID
 
 class EventType(str, Enum):
     # File events
@@ -36,6 +37,12 @@ class EventType(str, Enum):
     PROMPT_GENERATED = "prompt_generated"
     AI_SUGGESTION = "ai_suggestion"
 
+    # Developer events
+    NOTE_CAPTURED = "note_captured"
+
+    # Privacy events
+    TRACKING_GAP = "tracking_gap"
+
 class Agent(str, Enum):
     FILE = "file"
     EDITOR = "editor"
@@ -44,6 +51,7 @@ class Agent(str, Enum):
     FLOW = "flow"
     AI = "ai"
     BROWSER = "browser"
+    DEVELOPER = "developer"
 
 class ContextEvent(BaseModel):
     sessionId: str = Field(..., description="Session identifier")
@@ -53,6 +61,7 @@ class ContextEvent(BaseModel):
     timestamp: str = Field(..., description="ISO timestamp")
     userId: Optional[str] = Field(None, description="User identifier")
     projectId: Optional[str] = Field(None, description="Project identifier")
+    eventId: Optional[UUID] = Field(None, description="Idempotency key; an event id is stored once")
 
 class FlowSession(BaseModel):
     session_id: str
@@ -34,6 +34,7 @@ class AIService:
         editor_events = [e for e in events if e.agent == "editor"]
         terminal_events = [e for e in events if e.agent == "terminal"]
         git_events = [e for e in events if e.agent == "git"]
+        note_events = [e for e in events if e.type == "note_captured"]
 
         # Build context sections
         prompt_sections = [
@@ -46,6 +47,20 @@ class AIService:
             ""
         ]
 
+        # Developer notes state intent directly, so they lead the prompt
+        if note_events:
+            prompt_sections.extend([
+                "## Developer Notes",
+                ""
+            ])
+            for event in note_events[-10:]:
+                where = ", ".join(
+                    str(event.payload[k]) for k in ("project", "branch", "file_path") if event.payload.get(k)
+                )
+                suffix = f" ({where})" if where else ""
+                prompt_sections.append(f"- {event.timestamp}: {event.payload.get('note', '')}{suffix}")
+            prompt_sections.append("")
+
         # Recent file activity
         if file_events:
             prompt_sections.extend([
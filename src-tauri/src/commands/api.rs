@@ -0,0 +1,509 @@
+use super::context::{ContextEvent, ContextSession};
+use super::error::CommandResult;
+use super::store::JsonStore;
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tauri::State;
+
+const CONFIG_FILE: &str = "api_config.json";
+const DEFAULT_API_URL: &str = "http://localhost:8000/api/v1";
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "snake_case")]
+pub enum ApiError {
+    /// The backend could not be reached at all.
+    Unavailable(String),
+    /// The backend answered with a non-success status.
+    Status { code: u16, detail: String },
+    /// The response body did not match the expected shape.
+    Decode(String),
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiError::Unavailable(e) => write!(f, "Backend unavailable: {}", e),
+            ApiError::Status { code, detail } => write!(f, "HTTP {}: {}", code, detail),
+            ApiError::Decode(e) => write!(f, "Unexpected response: {}", e),
+        }
+    }
+}
+
+impl ApiError {
+    /// Whether retrying the same request later could succeed.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            ApiError::Unavailable(_) => true,
+            ApiError::Status { code, .. } => *code == 429 || *code >= 500,
+            ApiError::Decode(_) => false,
+        }
+    }
+
+    /// Whether the backend refused the request body itself, so sending it
+    /// again can never succeed. Auth and routing errors are not rejections.
+    pub fn is_rejection(&self) -> bool {
+        matches!(
+            self,
+            ApiError::Status {
+                code: 400 | 422,
+                ..
+            }
+        )
+    }
+}
+
+/// Mirrors `StuckState` in `apps/ccm-api/models/events.py`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StuckState {
+    pub session_id: String,
+    pub detected_at: DateTime<Utc>,
+    /// One of `looping_edits`, `repeated_errors`, `idle_with_failures`, or
+    /// `manual` when the user marked it themselves.
+    pub pattern: String,
+    pub context: serde_json::Value,
+    #[serde(default)]
+    pub resolved: bool,
+}
+
+/// Mirrors `CommitSuggestion` in `apps/ccm-api/models/events.py`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitSuggestion {
+    pub session_id: String,
+    pub message: String,
+    pub files: Vec<String>,
+    #[serde(default)]
+    pub auto_stage: bool,
+    pub confidence: f64,
+}
+
+/// Mirrors `BranchSuggestion` in `apps/ccm-api/models/events.py`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchSuggestion {
+    pub session_id: String,
+    pub branch_name: String,
+    pub reason: String,
+    pub confidence: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventReceipt {
+    pub status: String,
+    pub event_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchReceipt {
+    pub status: String,
+    #[serde(default)]
+    pub event_ids: Vec<String>,
+}
+
+/// Response of `GET /flow/state/{session_id}`, as built by
+/// `FlowService.detect_flow_state`. Scores are fractions from 0 to 1; a
+/// session without recent events only has `in_flow` and `confidence`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FlowState {
+    pub in_flow: bool,
+    /// The combined flow score.
+    pub confidence: f64,
+    pub activity_score: f64,
+    pub focus_score: f64,
+    pub momentum_score: f64,
+    pub keystroke_activity: u64,
+    pub context_switches: u64,
+    pub session_active: bool,
+}
+
+impl FlowState {
+    /// `focus_score` as a whole percentage, the form the tray and the focus
+    /// widget both show.
+    pub fn focus_percent(&self) -> u8 {
+        (self.focus_score.clamp(0.0, 1.0) * 100.0).round() as u8
+    }
+}
+
+/// Response of `GET /energy/score/{session_id}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnergyScore {
+    pub session_id: String,
+    pub energy_score: serde_json::Value,
+    #[serde(default)]
+    pub trends: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct XpAward {
+    pub session_id: String,
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct XpAwarded {
+    pub xp_earned: i64,
+    pub session_id: String,
+    pub source: String,
+}
+
+/// Stats returned under `profile` by `GET /gamification/profile/{session_id}`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserProfile {
+    pub id: String,
+    pub username: String,
+    pub total_xp: i64,
+    pub level: i64,
+    pub current_streak: i64,
+    pub longest_streak: i64,
+    pub total_commits: i64,
+    pub total_flow_time: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiConfig {
+    pub base_url: String,
+    pub auth_token: Option<String>,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        ApiConfig {
+            base_url: std::env::var("PULSEDEV_API_URL")
+                .unwrap_or_else(|_| DEFAULT_API_URL.to_string()),
+            auth_token: None,
+        }
+    }
+}
+
+/// Blocking client for the CCM API, shared by every native subsystem so they
+/// work while the webview is closed.
+pub struct CcmClient {
+    config: RwLock<ApiConfig>,
+    store: JsonStore,
+    http: reqwest::blocking::Client,
+    /// Whether the last request got an answer, of any status.
+    reachable: AtomicBool,
+}
+
+impl CcmClient {
+    pub fn load(data_dir: Option<PathBuf>) -> Arc<Self> {
+        let store = JsonStore::new(data_dir.as_deref(), CONFIG_FILE);
+        let config = store.load().unwrap_or_default();
+        Arc::new(CcmClient {
+            config: RwLock::new(config),
+            store,
+            http: reqwest::blocking::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .expect("failed to build HTTP client"),
+            reachable: AtomicBool::new(true),
+        })
+    }
+
+    pub fn config(&self) -> ApiConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    /// False while the backend cannot be reached at all; any response,
+    /// even an error status, counts as reachable.
+    pub fn reachable(&self) -> bool {
+        self.reachable.load(Ordering::Relaxed)
+    }
+
+    pub fn set_config(&self, config: ApiConfig) -> std::io::Result<()> {
+        self.store.save(&config)?;
+        *self.config.write().unwrap() = config;
+        Ok(())
+    }
+
+    pub fn post_event(&self, event: &ContextEvent) -> Result<EventReceipt, ApiError> {
+        self.send(
+            self.request(reqwest::Method::POST, "/context/events")
+                .json(event),
+        )
+    }
+
+    /// Stores `events` in one transaction: all of them or none.
+    pub fn post_events(&self, events: &[&ContextEvent]) -> Result<BatchReceipt, ApiError> {
+        self.send(
+            self.request(reqwest::Method::POST, "/context/events/batch")
+                .json(events),
+        )
+    }
+
+    /// Served by `api/routes.py`; the `/ccm/flow/state` variant in
+    /// `api/ccm_routes.py` belongs to a router `main.py` does not mount.
+    pub fn flow_state(&self, session_id: &str) -> Result<FlowState, ApiError> {
+        self.send(self.request(reqwest::Method::GET, &format!("/flow/state/{}", session_id)))
+    }
+
+    pub fn energy_score(&self, session_id: &str, hours: u32) -> Result<EnergyScore, ApiError> {
+        self.send(
+            self.request(
+                reqwest::Method::GET,
+                &format!("/energy/score/{}", session_id),
+            )
+            .query(&[("hours", hours)]),
+        )
+    }
+
+    pub fn award_xp(&self, award: &XpAward) -> Result<XpAwarded, ApiError> {
+        self.send(
+            self.request(reqwest::Method::POST, "/gamification/xp/award")
+                .json(award),
+        )
+    }
+
+    pub fn sync_session(
+        &self,
+        session_id: &str,
+        platform: &str,
+    ) -> Result<serde_json::Value, ApiError> {
+        let body = serde_json::json!({ "session_id": session_id, "platform": platform });
+        self.send::<Envelope>(
+            self.request(reqwest::Method::POST, "/gamification/session/sync")
+                .json(&body),
+        )
+        .map(|e| e.field("sync_data"))
+    }
+
+    pub fn profile(&self, session_id: &str) -> Result<UserProfile, ApiError> {
+        let envelope: Envelope = self.send(self.request(
+            reqwest::Method::GET,
+            &format!("/gamification/profile/{}", session_id),
+        ))?;
+        serde_json::from_value(envelope.field("profile"))
+            .map_err(|e| ApiError::Decode(e.to_string()))
+    }
+
+    pub fn achievements(&self, session_id: &str) -> Result<serde_json::Value, ApiError> {
+        self.send::<Envelope>(self.request(
+            reqwest::Method::GET,
+            &format!("/gamification/achievements/{}", session_id),
+        ))
+        .map(|e| e.field("achievements"))
+    }
+
+    pub fn leaderboard(&self, category: &str, period: &str) -> Result<serde_json::Value, ApiError> {
+        self.send::<Envelope>(
+            self.request(reqwest::Method::GET, "/gamification/leaderboard")
+                .query(&[("category", category), ("period", period)]),
+        )
+        .map(|e| e.field("leaderboard"))
+    }
+
+    pub fn track_activity(&self, activity: &serde_json::Value) -> Result<(), ApiError> {
+        self.send::<Envelope>(
+            self.request(reqwest::Method::POST, "/gamification/activity/track")
+                .json(activity),
+        )
+        .map(|_| ())
+    }
+
+    pub fn gamification_dashboard(&self, session_id: &str) -> Result<serde_json::Value, ApiError> {
+        self.send::<Envelope>(self.request(
+            reqwest::Method::GET,
+            &format!("/gamification/dashboard/{}", session_id),
+        ))
+        .map(|e| e.field("dashboard"))
+    }
+
+    fn send<T: DeserializeOwned>(
+        &self,
+        request: reqwest::blocking::RequestBuilder,
+    ) -> Result<T, ApiError> {
+        let result = send_request(request);
+        let reachable = !matches!(result, Err(ApiError::Unavailable(_)));
+        self.reachable.store(reachable, Ordering::Relaxed);
+        result
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::blocking::RequestBuilder {
+        let config = self.config.read().unwrap();
+        let url = format!("{}{}", config.base_url.trim_end_matches('/'), path);
+        let mut request = self.http.request(method, url);
+        if let Some(token) = &config.auth_token {
+            request = request.bearer_auth(token);
+        }
+        request
+    }
+}
+
+/// The gamification routes wrap their payload in `{ "success": true, ... }`.
+#[derive(Deserialize)]
+struct Envelope {
+    #[serde(flatten)]
+    fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Envelope {
+    fn field(mut self, name: &str) -> serde_json::Value {
+        self.fields.remove(name).unwrap_or(serde_json::Value::Null)
+    }
+}
+
+fn send_request<T: DeserializeOwned>(
+    request: reqwest::blocking::RequestBuilder,
+) -> Result<T, ApiError> {
+    let response = request
+        .send()
+        .map_err(|e| ApiError::Unavailable(e.to_string()))?;
+    let status = response.status();
+    if !status.is_success() {
+        let detail = response
+            .json::<serde_json::Value>()
+            .ok()
+            .and_then(|body| {
+                body.get("detail")
+                    .and_then(|d| d.as_str())
+                    .map(String::from)
+            })
+            .unwrap_or_else(|| status.to_string());
+        return Err(ApiError::Status {
+            code: status.as_u16(),
+            detail,
+        });
+    }
+    response.json().map_err(|e| ApiError::Decode(e.to_string()))
+}
+
+#[tauri::command]
+pub fn get_api_config(client: State<'_, Arc<CcmClient>>) -> ApiConfig {
+    client.config()
+}
+
+#[tauri::command]
+pub fn set_api_config(
+    base_url: String,
+    auth_token: Option<String>,
+    client: State<'_, Arc<CcmClient>>,
+) -> CommandResult<()> {
+    Ok(client.set_config(ApiConfig {
+        base_url,
+        auth_token,
+    })?)
+}
+
+/// `hours` defaults to the backend's own window of 8.
+#[tauri::command(async)]
+pub fn get_energy_score(
+    hours: Option<u32>,
+    client: State<'_, Arc<CcmClient>>,
+    session: State<'_, ContextSession>,
+) -> CommandResult<EnergyScore> {
+    Ok(client.energy_score(&session.id(), hours.unwrap_or(8))?)
+}
+
+#[tauri::command(async)]
+pub fn award_xp(
+    source: String,
+    amount: Option<i64>,
+    description: Option<String>,
+    metadata: Option<serde_json::Value>,
+    client: State<'_, Arc<CcmClient>>,
+    session: State<'_, ContextSession>,
+) -> CommandResult<XpAwarded> {
+    Ok(client.award_xp(&XpAward {
+        session_id: session.id(),
+        source,
+        amount,
+        description,
+        metadata,
+    })?)
+}
+
+#[tauri::command(async)]
+pub fn sync_gamification_session(
+    client: State<'_, Arc<CcmClient>>,
+    session: State<'_, ContextSession>,
+) -> CommandResult<serde_json::Value> {
+    Ok(client.sync_session(&session.id(), "desktop")?)
+}
+
+#[tauri::command(async)]
+pub fn get_achievements(
+    client: State<'_, Arc<CcmClient>>,
+    session: State<'_, ContextSession>,
+) -> CommandResult<serde_json::Value> {
+    Ok(client.achievements(&session.id())?)
+}
+
+#[tauri::command(async)]
+pub fn get_leaderboard(
+    category: Option<String>,
+    period: Option<String>,
+    client: State<'_, Arc<CcmClient>>,
+) -> CommandResult<serde_json::Value> {
+    Ok(client.leaderboard(
+        category.as_deref().unwrap_or("xp"),
+        period.as_deref().unwrap_or("weekly"),
+    )?)
+}
+
+/// Reports an activity of `activity_type` for the current session; the
+/// backend awards its XP in the background.
+#[tauri::command(async)]
+pub fn track_activity(
+    activity_type: String,
+    details: Option<serde_json::Map<String, serde_json::Value>>,
+    client: State<'_, Arc<CcmClient>>,
+    session: State<'_, ContextSession>,
+) -> CommandResult<()> {
+    let mut activity = details.unwrap_or_default();
+    activity.insert("session_id".into(), session.id().into());
+    activity.insert("type".into(), activity_type.into());
+    Ok(client.track_activity(&serde_json::Value::Object(activity))?)
+}
+
+#[tauri::command(async)]
+pub fn get_gamification_dashboard(
+    client: State<'_, Arc<CcmClient>>,
+    session: State<'_, ContextSession>,
+) -> CommandResult<serde_json::Value> {
+    Ok(client.gamification_dashboard(&session.id())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flow_state_decodes() {
+        let body = serde_json::json!({
+            "in_flow": true,
+            "confidence": 0.82,
+            "activity_score": 1.0,
+            "focus_score": 0.9,
+            "momentum_score": 0.33,
+            "keystroke_activity": 64,
+            "context_switches": 1,
+            "session_active": true
+        });
+        let state: FlowState = serde_json::from_value(body).unwrap();
+        assert!(state.in_flow);
+        assert_eq!(state.confidence, 0.82);
+        assert_eq!(state.focus_score, 0.9);
+        assert_eq!(state.focus_percent(), 90);
+        assert_eq!(state.keystroke_activity, 64);
+    }
+
+    #[test]
+    fn test_flow_state_decodes_without_events() {
+        let body = serde_json::json!({ "in_flow": false, "confidence": 0.0 });
+        let state: FlowState = serde_json::from_value(body).unwrap();
+        assert!(!state.in_flow);
+        assert_eq!(state.focus_score, 0.0);
+        assert!(!state.session_active);
+    }
+}
@@ -0,0 +1,334 @@
+use super::api::CommitSuggestion;
+use super::error::{CommandError, CommandResult};
+use super::git::{ensure_committable, open_in_scope, relative_path};
+use super::scope::WorkspaceScope;
+use super::store::JsonStore;
+use git2::{Delta, DiffOptions, Index, Oid, Patch, Repository, ResetType};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::State;
+
+const AUTO_COMMIT_FILE: &str = "last_auto_commit.json";
+/// Hooks `git commit` would run. Commits made through libgit2 skip them.
+const COMMIT_HOOKS: &[&str] = &[
+    "pre-commit",
+    "prepare-commit-msg",
+    "commit-msg",
+    "post-commit",
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileChange {
+    pub path: String,
+    /// `added`, `modified`, `deleted`, `renamed` or `typechange`.
+    pub status: String,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+/// What `execute_auto_commit` would do, without touching the repository.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommitPlan {
+    pub repo: String,
+    pub branch: String,
+    pub message: String,
+    pub files: Vec<FileChange>,
+    /// Requested files with nothing to commit; they are skipped.
+    pub unchanged: Vec<String>,
+    /// Other files that are staged. They stay staged and are not committed.
+    pub other_staged: Vec<String>,
+    /// Repository settings this commit would bypass: signing and commit
+    /// hooks. `execute_auto_commit` refuses while any are listed.
+    pub bypassed: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoCommit {
+    pub repo: String,
+    pub branch: String,
+    pub hash: String,
+    /// HEAD before the commit; `undo_auto_commit` resets back to it.
+    pub parent: String,
+    pub message: String,
+    pub files: Vec<String>,
+}
+
+/// The most recent auto-commit, kept on disk so it can still be undone
+/// after a restart.
+pub struct AutoCommits {
+    last: Mutex<Option<AutoCommit>>,
+    store: JsonStore,
+}
+
+impl AutoCommits {
+    pub fn load(data_dir: Option<PathBuf>) -> Self {
+        let store = JsonStore::new(data_dir.as_deref(), AUTO_COMMIT_FILE);
+        let last = store.load();
+        AutoCommits {
+            last: Mutex::new(last),
+            store,
+        }
+    }
+
+    fn set(&self, commit: Option<AutoCommit>) -> CommandResult<()> {
+        let mut last = self.last.lock().unwrap();
+        match &commit {
+            Some(commit) => self.store.save(commit)?,
+            None => self.store.remove()?,
+        }
+        *last = commit;
+        Ok(())
+    }
+}
+
+/// What `git commit` would do here that a libgit2 commit does not: sign it
+/// when `commit.gpgsign` is set, or run any of the commit hooks.
+fn bypassed_settings(repo: &Repository) -> CommandResult<Vec<String>> {
+    let config = repo.config()?;
+    let mut bypassed = Vec::new();
+    if config.get_bool("commit.gpgsign").unwrap_or(false) {
+        bypassed.push("commit.gpgsign is set; the commit would not be signed".to_string());
+    }
+    let hooks = match config.get_path("core.hooksPath") {
+        Ok(path) if path.is_absolute() => path,
+        Ok(path) => repo.workdir().unwrap_or(repo.path()).join(path),
+        Err(_) => repo.commondir().join("hooks"),
+    };
+    for hook in COMMIT_HOOKS {
+        if is_executable(&hooks.join(hook)) {
+            bypassed.push(format!("the {} hook would not run", hook));
+        }
+    }
+    Ok(bypassed)
+}
+
+/// Git only runs hooks it can execute, which skips the shipped `.sample`s
+/// and any that were never made executable.
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+fn status_name(delta: Delta) -> &'static str {
+    match delta {
+        Delta::Added | Delta::Untracked => "added",
+        Delta::Deleted => "deleted",
+        Delta::Renamed => "renamed",
+        Delta::Typechange => "typechange",
+        _ => "modified",
+    }
+}
+
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// Diff of `files` from HEAD to what would be committed: the working tree
+/// when staging is requested, otherwise the index.
+fn plan(
+    repo: &Repository,
+    work_tree: &Path,
+    suggestion: &CommitSuggestion,
+) -> CommandResult<CommitPlan> {
+    let branch = ensure_committable(repo)?;
+    if suggestion.message.trim().is_empty() {
+        return Err(CommandError::InvalidInput(
+            "Commit message is empty".to_string(),
+        ));
+    }
+    let files: Vec<PathBuf> = suggestion
+        .files
+        .iter()
+        .map(|f| relative_path(work_tree, f))
+        .collect::<CommandResult<_>>()?;
+    if files.is_empty() {
+        return Err(CommandError::InvalidInput("No files to commit".to_string()));
+    }
+
+    let head_tree = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+    let mut options = DiffOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .disable_pathspec_match(true);
+    for file in &files {
+        options.pathspec(file.as_path());
+    }
+    let diff = if suggestion.auto_stage {
+        repo.diff_tree_to_workdir_with_index(head_tree.as_ref(), Some(&mut options))?
+    } else {
+        repo.diff_tree_to_index(head_tree.as_ref(), None, Some(&mut options))?
+    };
+
+    let mut changes = Vec::new();
+    for (i, delta) in diff.deltas().enumerate() {
+        let Some(path) = delta.new_file().path().or(delta.old_file().path()) else {
+            continue;
+        };
+        let (_, insertions, deletions) = Patch::from_diff(&diff, i)?
+            .map(|p| p.line_stats())
+            .transpose()?
+            .unwrap_or_default();
+        changes.push(FileChange {
+            path: path_string(path),
+            status: status_name(delta.status()).to_string(),
+            insertions,
+            deletions,
+        });
+    }
+    let unchanged = files
+        .iter()
+        .map(|f| path_string(f))
+        .filter(|f| !changes.iter().any(|c| &c.path == f))
+        .collect();
+
+    let staged = repo.diff_tree_to_index(head_tree.as_ref(), None, None)?;
+    let other_staged = staged
+        .deltas()
+        .filter_map(|d| d.new_file().path().or(d.old_file().path()).map(path_string))
+        .filter(|p| !files.iter().any(|f| &path_string(f) == p))
+        .collect();
+
+    Ok(CommitPlan {
+        repo: work_tree.display().to_string(),
+        branch,
+        message: suggestion.message.trim().to_string(),
+        files: changes,
+        unchanged,
+        other_staged,
+        bypassed: bypassed_settings(repo)?,
+    })
+}
+
+/// Stages `plan.files` if asked to, then commits exactly those paths on top
+/// of HEAD with the identity from the user's git config. Other staged
+/// changes are left staged and out of the commit.
+fn commit(
+    repo: &Repository,
+    plan: &CommitPlan,
+    auto_stage: bool,
+) -> CommandResult<(Oid, Option<Oid>)> {
+    if plan.files.is_empty() {
+        return Err(CommandError::InvalidInput(
+            "None of the files have changes to commit".to_string(),
+        ));
+    }
+    if !plan.bypassed.is_empty() {
+        return Err(CommandError::Unsupported(format!(
+            "Commit with git instead: {}",
+            plan.bypassed.join(", ")
+        )));
+    }
+    let mut index = repo.index()?;
+    if auto_stage {
+        for change in &plan.files {
+            let path = Path::new(&change.path);
+            if change.status == "deleted" {
+                index.remove_path(path)?;
+            } else {
+                index.add_path(path)?;
+            }
+        }
+        index.write()?;
+    }
+
+    let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    let mut partial = Index::new()?;
+    if let Some(parent) = &parent {
+        partial.read_tree(&parent.tree()?)?;
+    }
+    for change in &plan.files {
+        let path = Path::new(&change.path);
+        match index.get_path(path, 0) {
+            Some(entry) => partial.add(&entry)?,
+            None => {
+                let _ = partial.remove_path(path);
+            }
+        }
+    }
+    let tree = repo.find_tree(partial.write_tree_to(repo)?)?;
+    let signature = repo.signature().map_err(|_| {
+        CommandError::InvalidInput("Set user.name and user.email in git config".to_string())
+    })?;
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    let oid = repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        &plan.message,
+        &tree,
+        &parents,
+    )?;
+    Ok((oid, parent.map(|p| p.id())))
+}
+
+/// Shows what committing `suggestion` in the repository at `repo` would do.
+#[tauri::command]
+pub fn auto_commit_dry_run(
+    repo: String,
+    suggestion: CommitSuggestion,
+    scope: State<'_, WorkspaceScope>,
+) -> CommandResult<CommitPlan> {
+    let (located, repository) = open_in_scope(&scope, &repo)?;
+    plan(&repository, &located.work_tree, &suggestion)
+}
+
+#[tauri::command]
+pub fn execute_auto_commit(
+    repo: String,
+    suggestion: CommitSuggestion,
+    scope: State<'_, WorkspaceScope>,
+    commits: State<'_, AutoCommits>,
+) -> CommandResult<AutoCommit> {
+    let (located, repository) = open_in_scope(&scope, &repo)?;
+    let plan = plan(&repository, &located.work_tree, &suggestion)?;
+    let (oid, parent) = commit(&repository, &plan, suggestion.auto_stage)?;
+    let record = AutoCommit {
+        repo: plan.repo,
+        branch: plan.branch,
+        hash: oid.to_string(),
+        // A root commit cannot be soft-reset; leave it unrecorded for undo.
+        parent: parent.map(|p| p.to_string()).unwrap_or_default(),
+        message: plan.message,
+        files: plan.files.into_iter().map(|f| f.path).collect(),
+    };
+    commits.set((!record.parent.is_empty()).then(|| record.clone()))?;
+    Ok(record)
+}
+
+/// Undoes the last auto-commit with a soft reset, keeping its changes
+/// staged. Refused once anything else has moved the branch.
+#[tauri::command]
+pub fn undo_auto_commit(
+    scope: State<'_, WorkspaceScope>,
+    commits: State<'_, AutoCommits>,
+) -> CommandResult<AutoCommit> {
+    let last = commits
+        .last
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or_else(|| CommandError::NotFound("No auto-commit to undo".to_string()))?;
+    let (_, repository) = open_in_scope(&scope, &last.repo)?;
+    let branch = ensure_committable(&repository)?;
+    let head = repository.head()?.target().map(|oid| oid.to_string());
+    if branch != last.branch || head.as_deref() != Some(last.hash.as_str()) {
+        commits.set(None)?;
+        return Err(CommandError::InvalidInput(format!(
+            "{} has moved since the auto-commit; undo it with git instead",
+            last.branch
+        )));
+    }
+    let parent = repository.find_object(Oid::from_str(&last.parent)?, None)?;
+    repository.reset(&parent, ResetType::Soft, None)?;
+    commits.set(None)?;
+    Ok(last)
+}
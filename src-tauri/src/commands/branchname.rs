@@ -0,0 +1,472 @@
+use super::api::BranchSuggestion;
+use super::context::ContextSession;
+use super::error::{CommandError, CommandResult};
+use super::git::{ensure_committable, open_in_scope};
+use super::scope::WorkspaceScope;
+use super::store::JsonStore;
+use git2::{Branch, BranchType, Repository};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::State;
+
+const BRANCH_CONFIG_FILE: &str = "branch_naming.json";
+/// Suffixes tried (`-2` …) before giving up on a colliding name.
+const MAX_SUFFIX: u32 = 9;
+const STOPWORDS: &[&str] = &[
+    "a", "an", "the", "to", "for", "of", "and", "or", "in", "on", "with", "when", "from", "is",
+    "be", "as", "at", "by", "it", "this", "that", "should",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BranchNaming {
+    /// Placeholders: `{type}`, `{ticket}` and `{slug}`. Segments left empty
+    /// (no ticket) are dropped along with their separator.
+    pub template: String,
+    pub max_slug_words: usize,
+    pub max_length: usize,
+}
+
+impl Default for BranchNaming {
+    fn default() -> Self {
+        BranchNaming {
+            template: "{type}/{ticket}-{slug}".to_string(),
+            max_slug_words: 5,
+            max_length: 60,
+        }
+    }
+}
+
+impl BranchNaming {
+    fn render(&self, kind: &str, ticket: Option<&str>, slug: &str) -> String {
+        let rendered = self
+            .template
+            .replace("{type}", kind)
+            .replace("{ticket}", ticket.unwrap_or(""))
+            .replace("{slug}", slug);
+        // Collapse the separators around an empty placeholder.
+        let mut name = String::new();
+        for c in rendered.chars() {
+            let separator = matches!(c, '-' | '_' | '/');
+            let after_separator = name.is_empty() || name.ends_with(['-', '_', '/']);
+            if separator && after_separator {
+                if c == '/' && name.ends_with(['-', '_']) {
+                    name.pop();
+                    name.push('/');
+                }
+                continue;
+            }
+            name.push(c);
+        }
+        let mut name: String = name.chars().take(self.max_length).collect();
+        while name.ends_with(['-', '_', '/']) {
+            name.pop();
+        }
+        name
+    }
+}
+
+pub struct BranchNamingConfig {
+    config: Mutex<BranchNaming>,
+    store: JsonStore,
+}
+
+impl BranchNamingConfig {
+    pub fn load(data_dir: Option<PathBuf>) -> Self {
+        let store = JsonStore::new(data_dir.as_deref(), BRANCH_CONFIG_FILE);
+        let config = store.load().unwrap_or_default();
+        BranchNamingConfig {
+            config: Mutex::new(config),
+            store,
+        }
+    }
+
+    pub fn config(&self) -> BranchNaming {
+        self.config.lock().unwrap().clone()
+    }
+
+    pub fn set_config(&self, config: BranchNaming) -> CommandResult<()> {
+        if !config.template.contains("{slug}") {
+            return Err(CommandError::InvalidInput(
+                "The template needs a {slug} placeholder".to_string(),
+            ));
+        }
+        self.store.save(&config)?;
+        *self.config.lock().unwrap() = config;
+        Ok(())
+    }
+}
+
+/// What the suggestion is based on; every field is optional.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct BranchContext {
+    /// Story or ticket title, e.g. `PROJ-123 Retry uploads when offline`.
+    pub title: Option<String>,
+    /// Ticket key; found in the title or notes when not given.
+    pub ticket: Option<String>,
+    /// Recent quick-capture notes, newest first.
+    pub notes: Vec<String>,
+    pub files: Vec<String>,
+}
+
+/// A ticket key like `PROJ-123`: uppercase letters and digits starting with
+/// a letter, a dash, then digits.
+fn find_ticket(text: &str) -> Option<String> {
+    text.split(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+        .find(|word| {
+            let Some((key, number)) = word.split_once('-') else {
+                return false;
+            };
+            key.len() >= 2
+                && key.starts_with(|c: char| c.is_ascii_uppercase())
+                && key
+                    .chars()
+                    .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+                && !number.is_empty()
+                && number.chars().all(|c| c.is_ascii_digit())
+        })
+        .map(str::to_string)
+}
+
+fn branch_type(text: &str) -> &'static str {
+    let lower = text.to_lowercase();
+    let has = |words: &[&str]| {
+        lower
+            .split(|c: char| !c.is_alphanumeric())
+            .any(|w| words.contains(&w))
+    };
+    if has(&[
+        "fix",
+        "bug",
+        "bugfix",
+        "error",
+        "crash",
+        "broken",
+        "regression",
+    ]) {
+        "fix"
+    } else if has(&["refactor", "cleanup", "restructure", "rename"]) {
+        "refactor"
+    } else if has(&["docs", "documentation", "readme"]) {
+        "docs"
+    } else if has(&["test", "tests", "coverage"]) {
+        "test"
+    } else if has(&["chore", "bump", "upgrade", "deps"]) {
+        "chore"
+    } else {
+        "feat"
+    }
+}
+
+fn slugify(text: &str, ticket: Option<&str>, max_words: usize) -> String {
+    let ticket = ticket.map(str::to_lowercase);
+    text.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty() && !STOPWORDS.contains(w))
+        // Drop the ticket key, which the split leaves as `proj` + `123`.
+        .filter(|w| {
+            !ticket
+                .as_deref()
+                .is_some_and(|t| t.split('-').any(|part| part == *w))
+        })
+        .take(max_words)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Slug words from the names of the changed files.
+fn files_slug(files: &[String], max_words: usize) -> Option<String> {
+    let names: BTreeSet<String> = files
+        .iter()
+        .filter_map(|f| Path::new(f).file_stem())
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .filter(|s| s != "mod" && s != "index")
+        .collect();
+    let text = names.into_iter().collect::<Vec<_>>().join(" ");
+    let slug = slugify(&text, None, max_words.min(3));
+    (!slug.is_empty()).then_some(slug)
+}
+
+/// Whether refs named `a` and `b` cannot both exist: they are equal, or one
+/// is a directory of the other (`feat` and `feat/x`).
+fn refs_clash(a: &str, b: &str) -> bool {
+    let nested = |outer: &str, inner: &str| {
+        inner
+            .strip_prefix(outer)
+            .is_some_and(|rest| rest.starts_with('/'))
+    };
+    a == b || nested(a, b) || nested(b, a)
+}
+
+/// Whether `name` is taken, or clashes with a branch, locally or on a
+/// remote.
+fn branch_exists(repo: &Repository, name: &str) -> bool {
+    let Ok(branches) = repo.branches(None) else {
+        return false;
+    };
+    branches.flatten().any(|(branch, kind)| {
+        let Ok(Some(full)) = branch.name() else {
+            return false;
+        };
+        let short = match kind {
+            BranchType::Local => Some(full),
+            BranchType::Remote => full.split_once('/').map(|(_, short)| short),
+        };
+        short.is_some_and(|short| refs_clash(short, name))
+    })
+}
+
+/// `name` with `-N` appended, shortened first so the result still fits in
+/// `max_length`.
+fn with_suffix(name: &str, n: u32, max_length: usize) -> String {
+    let suffix = format!("-{}", n);
+    let mut base: String = name
+        .chars()
+        .take(max_length.saturating_sub(suffix.len()))
+        .collect();
+    while base.ends_with(['-', '_', '/']) {
+        base.pop();
+    }
+    base + &suffix
+}
+
+/// `name`, or the first `name-N` not already taken locally or on a remote.
+fn free_name(repo: &Repository, name: &str, max_length: usize) -> Option<String> {
+    if !branch_exists(repo, name) {
+        return Some(name.to_string());
+    }
+    (2..=MAX_SUFFIX)
+        .map(|n| with_suffix(name, n, max_length))
+        .find(|candidate| !branch_exists(repo, candidate))
+}
+
+pub fn suggest(
+    repo: &Repository,
+    naming: &BranchNaming,
+    context: &BranchContext,
+    session_id: &str,
+) -> Vec<BranchSuggestion> {
+    let ticket = context
+        .ticket
+        .clone()
+        .or_else(|| context.title.as_deref().and_then(find_ticket))
+        .or_else(|| context.notes.iter().find_map(|n| find_ticket(n)));
+    let type_source = context
+        .title
+        .clone()
+        .or_else(|| context.notes.first().cloned())
+        .unwrap_or_default();
+    let kind = branch_type(&type_source);
+
+    let mut sources: Vec<(String, &str, f64)> = Vec::new();
+    if let Some(title) = &context.title {
+        let slug = slugify(title, ticket.as_deref(), naming.max_slug_words);
+        let confidence = if ticket.is_some() { 0.9 } else { 0.8 };
+        sources.push((slug, "ticket title", confidence));
+    }
+    if let Some(note) = context.notes.first() {
+        let slug = slugify(note, ticket.as_deref(), naming.max_slug_words);
+        sources.push((slug, "latest note", 0.6));
+    }
+    if let Some(slug) = files_slug(&context.files, naming.max_slug_words) {
+        sources.push((slug, "changed files", 0.4));
+    }
+
+    let mut seen = BTreeSet::new();
+    let mut suggestions = Vec::new();
+    for (slug, source, confidence) in sources {
+        if slug.is_empty() {
+            continue;
+        }
+        let name = naming.render(kind, ticket.as_deref(), &slug);
+        if !Branch::name_is_valid(&name).unwrap_or(false) || !seen.insert(name.clone()) {
+            continue;
+        }
+        let Some(free) = free_name(repo, &name, naming.max_length) else {
+            continue;
+        };
+        let (reason, confidence) = if free == name {
+            (format!("From the {}", source), confidence)
+        } else {
+            (
+                format!("From the {}; {} already exists", source, name),
+                confidence - 0.1,
+            )
+        };
+        suggestions.push(BranchSuggestion {
+            session_id: session_id.to_string(),
+            branch_name: free,
+            reason,
+            confidence,
+        });
+    }
+    suggestions
+}
+
+#[tauri::command]
+pub fn suggest_branch_names(
+    repo: String,
+    context: BranchContext,
+    scope: State<'_, WorkspaceScope>,
+    naming: State<'_, BranchNamingConfig>,
+    session: State<'_, ContextSession>,
+) -> CommandResult<Vec<BranchSuggestion>> {
+    let (_, repository) = open_in_scope(&scope, &repo)?;
+    Ok(suggest(
+        &repository,
+        &naming.config(),
+        &context,
+        &session.id(),
+    ))
+}
+
+/// Creates `name` at HEAD and, if asked, switches to it. The working tree
+/// is untouched since the new branch points at the current commit.
+#[tauri::command]
+pub fn create_branch(
+    repo: String,
+    name: String,
+    switch: bool,
+    scope: State<'_, WorkspaceScope>,
+) -> CommandResult<String> {
+    let (_, repository) = open_in_scope(&scope, &repo)?;
+    if switch {
+        ensure_committable(&repository)?;
+    }
+    if !Branch::name_is_valid(&name)? {
+        return Err(CommandError::InvalidInput(format!(
+            "'{}' is not a valid branch name",
+            name
+        )));
+    }
+    if branch_exists(&repository, &name) {
+        return Err(CommandError::Conflict(format!(
+            "'{}' is taken or clashes with an existing branch",
+            name
+        )));
+    }
+    let head = repository.head()?.peel_to_commit()?;
+    let branch = repository.branch(&name, &head, false)?;
+    if switch {
+        let reference = branch
+            .get()
+            .name()
+            .ok_or_else(|| CommandError::InvalidInput(name.clone()))?;
+        repository.set_head(reference)?;
+    }
+    Ok(name)
+}
+
+#[tauri::command]
+pub fn get_branch_naming(naming: State<'_, BranchNamingConfig>) -> BranchNaming {
+    naming.config()
+}
+
+#[tauri::command]
+pub fn set_branch_naming(
+    config: BranchNaming,
+    naming: State<'_, BranchNamingConfig>,
+) -> CommandResult<()> {
+    naming.set_config(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_with_ticket() {
+        let naming = BranchNaming {
+            template: "feat/{ticket}-{slug}".to_string(),
+            ..BranchNaming::default()
+        };
+        assert_eq!(
+            naming.render("feat", Some("PROJ-123"), "short-slug"),
+            "feat/PROJ-123-short-slug"
+        );
+    }
+
+    #[test]
+    fn test_render_collapses_separator_without_ticket() {
+        let naming = BranchNaming::default();
+        assert_eq!(
+            naming.render("fix", None, "retry-uploads"),
+            "fix/retry-uploads"
+        );
+        let naming = BranchNaming {
+            template: "{ticket}_{type}_{slug}".to_string(),
+            ..BranchNaming::default()
+        };
+        assert_eq!(naming.render("fix", None, "retry"), "fix_retry");
+    }
+
+    #[test]
+    fn test_render_truncates_without_trailing_separator() {
+        let naming = BranchNaming {
+            max_length: 14,
+            ..BranchNaming::default()
+        };
+        assert_eq!(
+            naming.render("feat", None, "retry-uploads"),
+            "feat/retry-upl"
+        );
+        let naming = BranchNaming {
+            max_length: 13,
+            ..BranchNaming::default()
+        };
+        assert_eq!(
+            naming.render("feat", None, "retry-x-uploads"),
+            "feat/retry-x"
+        );
+    }
+
+    #[test]
+    fn test_find_ticket() {
+        assert_eq!(
+            find_ticket("PROJ-123 Retry uploads").as_deref(),
+            Some("PROJ-123")
+        );
+        assert_eq!(
+            find_ticket("see (AB2-7), not ab-1").as_deref(),
+            Some("AB2-7")
+        );
+        assert_eq!(find_ticket("retry uploads when offline"), None);
+        assert_eq!(find_ticket("A-1 X-2 PROJ-"), None);
+        assert_eq!(find_ticket("2FA-12 off-by-one"), None);
+    }
+
+    #[test]
+    fn test_slugify_drops_stopwords_and_ticket() {
+        assert_eq!(
+            slugify(
+                "PROJ-123: Retry the uploads when offline!",
+                Some("PROJ-123"),
+                5
+            ),
+            "retry-uploads-offline"
+        );
+        assert_eq!(slugify("Fix a crash in the parser", None, 2), "fix-crash");
+        assert_eq!(slugify("the of and", None, 5), "");
+    }
+
+    #[test]
+    fn test_refs_clash() {
+        assert!(refs_clash("feat", "feat"));
+        assert!(refs_clash("feat", "feat/x"));
+        assert!(refs_clash("feat/x/y", "feat/x"));
+        assert!(!refs_clash("feat", "feature/x"));
+        assert!(!refs_clash("feat/x", "feat/y"));
+    }
+
+    #[test]
+    fn test_suffix_fits_max_length() {
+        assert_eq!(with_suffix("feat/retry", 2, 60), "feat/retry-2");
+        let name = with_suffix("feat/retry-uploads", 3, 18);
+        assert_eq!(name, "feat/retry-uploa-3");
+        assert!(name.len() <= 18);
+        assert_eq!(with_suffix("feat/retry-uploads", 2, 13), "feat/retry-2");
+    }
+}
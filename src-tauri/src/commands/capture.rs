@@ -0,0 +1,102 @@
+use super::context::{record_event, Agent, ContextEvent, ContextSession, EventType};
+use super::error::{CommandError, CommandResult};
+use super::git::find_repo;
+use super::scope::WorkspaceScope;
+use super::tracking::Tracking;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager, WindowBuilder, WindowUrl};
+
+const CAPTURE_LABEL: &str = "capture";
+const CAPTURE_ROUTE: &str = "index.html#/capture";
+const MAX_NOTE_CHARS: usize = 2000;
+
+/// Opens the quick-capture box centred on screen, or focuses it if open.
+pub fn open_quick_capture(app: &AppHandle) -> CommandResult<()> {
+    if let Some(window) = app.get_window(CAPTURE_LABEL) {
+        window.show()?;
+        window.set_focus()?;
+        return Ok(());
+    }
+    WindowBuilder::new(app, CAPTURE_LABEL, WindowUrl::App(CAPTURE_ROUTE.into()))
+        .title("Quick capture")
+        .inner_size(480.0, 96.0)
+        .resizable(false)
+        .decorations(false)
+        .skip_taskbar(true)
+        .always_on_top(true)
+        .center()
+        .focused(true)
+        .build()?;
+    Ok(())
+}
+
+/// The workspace the developer is in: the granted root holding the file they
+/// last touched, or the first root when nothing has been touched yet.
+fn active_project(app: &AppHandle, current_file: Option<&PathBuf>) -> Option<PathBuf> {
+    let roots = app.state::<WorkspaceScope>().roots();
+    current_file
+        .and_then(|file| roots.iter().find(|root| file.starts_with(root)))
+        .or_else(|| roots.first())
+        .cloned()
+}
+
+/// Records `note` as a context event stamped with the active project, its
+/// git branch and the current file.
+pub fn capture_note(app: &AppHandle, note: &str) -> CommandResult<ContextEvent> {
+    let note = note.trim();
+    if note.is_empty() {
+        return Err(CommandError::InvalidInput("Note is empty".to_string()));
+    }
+    if note.chars().count() > MAX_NOTE_CHARS {
+        return Err(CommandError::InvalidInput(format!(
+            "Notes are limited to {} characters",
+            MAX_NOTE_CHARS
+        )));
+    }
+    if app.state::<Tracking>().is_paused() {
+        return Err(CommandError::Unsupported(
+            "Tracking is paused; the note was not recorded".to_string(),
+        ));
+    }
+
+    let session = app.state::<ContextSession>();
+    let current_file = session.current_file();
+    let project = active_project(app, current_file.as_ref());
+    let branch = project
+        .as_ref()
+        .or(current_file.as_ref())
+        .and_then(|p| find_repo(p))
+        .and_then(|repo| repo.describe_head());
+
+    let event = ContextEvent::new(
+        &session.id(),
+        Agent::Developer,
+        EventType::NoteCaptured,
+        serde_json::json!({
+            "note": note,
+            "project": project
+                .as_ref()
+                .and_then(|p| p.file_name())
+                .map(|n| n.to_string_lossy().to_string()),
+            "project_path": project.map(|p| p.display().to_string()),
+            "branch": branch,
+            "file_path": current_file.map(|p| p.display().to_string()),
+        }),
+    );
+    record_event(app, event.clone());
+    Ok(event)
+}
+
+#[tauri::command]
+pub fn open_quick_capture_window(app: AppHandle) -> CommandResult<()> {
+    open_quick_capture(&app)
+}
+
+#[tauri::command]
+pub fn submit_quick_capture(note: String, app: AppHandle) -> CommandResult<ContextEvent> {
+    let event = capture_note(&app, &note)?;
+    if let Some(window) = app.get_window(CAPTURE_LABEL) {
+        let _ = window.close();
+    }
+    Ok(event)
+}
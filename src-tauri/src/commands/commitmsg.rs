@@ -0,0 +1,462 @@
+use super::api::CommitSuggestion;
+use super::context::ContextSession;
+use super::error::{CommandError, CommandResult};
+use super::git::open_in_scope;
+use super::scope::WorkspaceScope;
+use git2::{Delta, DiffFindOptions, DiffFormat, Repository};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use tauri::State;
+
+/// Conventional-commit headers are kept within this many characters.
+const MAX_HEADER: usize = 72;
+const MAX_BODY_FILES: usize = 15;
+/// Keywords whose following identifier is a definition worth naming. Words
+/// as common in prose as in code, like `type`, are left out.
+const DEFINITION_KEYWORDS: &[&str] = &[
+    "fn",
+    "def",
+    "class",
+    "struct",
+    "enum",
+    "trait",
+    "interface",
+    "function",
+];
+/// Words that may precede a definition keyword at the start of a line.
+const DEFINITION_MODIFIERS: &[&str] = &[
+    "pub", "crate", "super", "export", "default", "async", "abstract", "declare", "unsafe",
+    "const", "extern",
+];
+/// Directory names too generic to serve as a scope.
+const GENERIC_DIRS: &[&str] = &[
+    "src",
+    "lib",
+    "app",
+    "apps",
+    "packages",
+    "crates",
+    "components",
+    "pages",
+    "utils",
+    "services",
+    "commands",
+    "api",
+    "models",
+];
+
+#[derive(Default)]
+struct StagedFile {
+    status: Option<Delta>,
+    insertions: usize,
+    deletions: usize,
+    added_symbols: BTreeSet<String>,
+    removed_symbols: BTreeSet<String>,
+}
+
+/// Per-file line counts and defined symbols of the staged diff. Line
+/// contents are only scanned here and never leave the machine.
+fn read_staged(repo: &Repository) -> CommandResult<BTreeMap<String, StagedFile>> {
+    let head_tree = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+    let mut diff = repo.diff_tree_to_index(head_tree.as_ref(), None, None)?;
+    diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+
+    let mut files: BTreeMap<String, StagedFile> = BTreeMap::new();
+    diff.print(DiffFormat::Patch, |delta, _hunk, line| {
+        let Some(path) = delta.new_file().path().or(delta.old_file().path()) else {
+            return true;
+        };
+        let file = files
+            .entry(path.to_string_lossy().replace('\\', "/"))
+            .or_default();
+        file.status = Some(delta.status());
+        let text = String::from_utf8_lossy(line.content());
+        match line.origin() {
+            '+' => {
+                file.insertions += 1;
+                file.added_symbols.extend(definition(&text));
+            }
+            '-' => {
+                file.deletions += 1;
+                file.removed_symbols.extend(definition(&text));
+            }
+            _ => {}
+        }
+        true
+    })?;
+    // Binary files and pure renames print no lines but are still staged.
+    for delta in diff.deltas() {
+        if let Some(path) = delta.new_file().path().or(delta.old_file().path()) {
+            let file = files
+                .entry(path.to_string_lossy().replace('\\', "/"))
+                .or_default();
+            file.status = Some(delta.status());
+        }
+    }
+    Ok(files)
+}
+
+/// The name a code line defines, e.g. `parse` in `pub fn parse(` or `Foo`
+/// in `export class Foo {`. The keyword must open the line, after any
+/// modifiers, so comments, strings and prose mentioning it are ignored.
+fn definition(line: &str) -> Option<String> {
+    let line = line.trim_start();
+    if !line.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let mut tokens = line
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|t| !t.is_empty())
+        .skip_while(|t| DEFINITION_MODIFIERS.contains(t));
+    let keyword = tokens.next()?;
+    let name = tokens.next()?;
+    (DEFINITION_KEYWORDS.contains(&keyword)
+        && !DEFINITION_KEYWORDS.contains(&name)
+        && !name.starts_with(|c: char| c.is_ascii_digit()))
+    .then(|| name.to_string())
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum PathKind {
+    Test,
+    Docs,
+    Ci,
+    Build,
+    Style,
+    Source,
+}
+
+fn path_kind(path: &str) -> PathKind {
+    let lower = path.to_lowercase();
+    let name = lower.rsplit('/').next().unwrap_or(&lower);
+    if lower.contains("/tests/")
+        || lower.starts_with("tests/")
+        || lower.contains("__tests__")
+        || name.contains(".test.")
+        || name.contains(".spec.")
+        || name.starts_with("test_")
+    {
+        PathKind::Test
+    } else if name.ends_with(".md") || lower.starts_with("docs/") {
+        PathKind::Docs
+    } else if lower.starts_with(".github/") || lower.starts_with(".gitlab") {
+        PathKind::Ci
+    } else if matches!(
+        name,
+        "cargo.toml"
+            | "cargo.lock"
+            | "package.json"
+            | "package-lock.json"
+            | "bun.lockb"
+            | "requirements.txt"
+            | "dockerfile"
+            | "docker-compose.yml"
+    ) || name.starts_with("vite.config")
+        || name.starts_with("tsconfig")
+    {
+        PathKind::Build
+    } else if name.ends_with(".css") || name.ends_with(".scss") {
+        PathKind::Style
+    } else {
+        PathKind::Source
+    }
+}
+
+/// The commit type, and whether every path agreed on it.
+fn infer_type(files: &BTreeMap<String, StagedFile>) -> (&'static str, bool) {
+    let kinds: Vec<PathKind> = files.keys().map(|p| path_kind(p)).collect();
+    let unanimous = |kind| kinds.iter().all(|k| *k == kind);
+    for (kind, name) in [
+        (PathKind::Test, "test"),
+        (PathKind::Docs, "docs"),
+        (PathKind::Ci, "ci"),
+        (PathKind::Build, "build"),
+        (PathKind::Style, "style"),
+    ] {
+        if unanimous(kind) {
+            return (name, true);
+        }
+    }
+
+    let added: usize = files.values().map(|f| f.insertions).sum();
+    let removed: usize = files.values().map(|f| f.deletions).sum();
+    let new_symbols = files.values().any(|f| {
+        f.added_symbols
+            .difference(&f.removed_symbols)
+            .next()
+            .is_some()
+    });
+    let all_new = files.values().all(|f| f.status == Some(Delta::Added));
+    if all_new || new_symbols {
+        ("feat", all_new)
+    } else if files
+        .values()
+        .all(|f| f.status == Some(Delta::Renamed) && f.insertions + f.deletions == 0)
+        || removed > added * 2
+    {
+        ("refactor", false)
+    } else if added + removed <= 20 {
+        ("fix", false)
+    } else {
+        ("chore", false)
+    }
+}
+
+/// The most specific directory shared by every path that is not a generic
+/// container like `src`, or the file stem when a single file is staged.
+fn infer_scope(paths: &[&str]) -> Option<String> {
+    if let [only] = paths {
+        return Path::new(only)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_lowercase())
+            .filter(|s| !s.is_empty() && s != "mod" && s != "index");
+    }
+    let split: Vec<Vec<&str>> = paths
+        .iter()
+        .map(|p| {
+            let mut parts: Vec<&str> = p.split('/').collect();
+            parts.pop();
+            parts
+        })
+        .collect();
+    let first = split.first()?;
+    let shared = (0..first.len())
+        .take_while(|&i| split.iter().all(|parts| parts.get(i) == first.get(i)))
+        .count();
+    first[..shared]
+        .iter()
+        .rev()
+        .find(|dir| !GENERIC_DIRS.contains(*dir) && !dir.starts_with('.'))
+        .map(|dir| dir.to_lowercase())
+}
+
+fn list(names: &[&str]) -> String {
+    match names {
+        [] => String::new(),
+        [one] => one.to_string(),
+        [a, b] => format!("{} and {}", a, b),
+        [a, b, rest @ ..] => format!("{}, {} and {} more", a, b, rest.len()),
+    }
+}
+
+/// Summary line from the symbols the diff adds, changes or removes, falling
+/// back to the file names. Returns whether symbols were found.
+fn summarize(files: &BTreeMap<String, StagedFile>) -> (String, bool) {
+    let mut added = BTreeSet::new();
+    let mut changed = BTreeSet::new();
+    let mut removed = BTreeSet::new();
+    for file in files.values() {
+        for name in &file.added_symbols {
+            if file.removed_symbols.contains(name) {
+                changed.insert(name.as_str());
+            } else {
+                added.insert(name.as_str());
+            }
+        }
+        removed.extend(file.removed_symbols.difference(&file.added_symbols));
+    }
+    let added: Vec<&str> = added.into_iter().collect();
+    let changed: Vec<&str> = changed.into_iter().collect();
+    let removed: Vec<&str> = removed.into_iter().map(String::as_str).collect();
+
+    if !added.is_empty() {
+        (format!("add {}", list(&added)), true)
+    } else if !changed.is_empty() {
+        (format!("update {}", list(&changed)), true)
+    } else if !removed.is_empty() {
+        (format!("remove {}", list(&removed)), true)
+    } else {
+        let names: Vec<&str> = files
+            .keys()
+            .map(|p| p.rsplit('/').next().unwrap_or(p))
+            .collect();
+        let verb = if files.values().all(|f| f.status == Some(Delta::Added)) {
+            "add"
+        } else if files.values().all(|f| f.status == Some(Delta::Deleted)) {
+            "remove"
+        } else {
+            "update"
+        };
+        (format!("{} {}", verb, list(&names)), false)
+    }
+}
+
+fn truncate(header: String) -> String {
+    if header.chars().count() <= MAX_HEADER {
+        return header;
+    }
+    let cut: String = header.chars().take(MAX_HEADER - 1).collect();
+    format!("{}…", cut.trim_end())
+}
+
+/// Drafts a conventional commit message for what is staged in `repo`.
+pub fn draft(repo: &Repository, session_id: &str) -> CommandResult<CommitSuggestion> {
+    let files = read_staged(repo)?;
+    if files.is_empty() {
+        return Err(CommandError::InvalidInput("Nothing is staged".to_string()));
+    }
+    let paths: Vec<&str> = files.keys().map(String::as_str).collect();
+    let (commit_type, type_certain) = infer_type(&files);
+    let scope = infer_scope(&paths);
+    let (summary, from_symbols) = summarize(&files);
+
+    let header = truncate(match &scope {
+        Some(scope) => format!("{}({}): {}", commit_type, scope, summary),
+        None => format!("{}: {}", commit_type, summary),
+    });
+    let mut body: Vec<String> = files
+        .iter()
+        .take(MAX_BODY_FILES)
+        .map(|(path, f)| format!("- {} (+{} -{})", path, f.insertions, f.deletions))
+        .collect();
+    if files.len() > MAX_BODY_FILES {
+        body.push(format!("- …and {} more", files.len() - MAX_BODY_FILES));
+    }
+
+    let mut confidence: f64 = 0.4;
+    if type_certain {
+        confidence += 0.2;
+    }
+    if from_symbols {
+        confidence += 0.2;
+    }
+    if scope.is_some() {
+        confidence += 0.1;
+    }
+    // Large changes rarely fit one summary line.
+    if files.len() > 10 {
+        confidence -= 0.2;
+    }
+
+    Ok(CommitSuggestion {
+        session_id: session_id.to_string(),
+        message: format!("{}\n\n{}", header, body.join("\n")),
+        files: files.into_keys().collect(),
+        // Drafted from the index, so everything is already staged.
+        auto_stage: false,
+        confidence: confidence.clamp(0.1, 0.95),
+    })
+}
+
+/// Drafts a commit message from the staged diff without contacting the
+/// backend.
+#[tauri::command]
+pub fn draft_commit_message(
+    repo: String,
+    scope: State<'_, WorkspaceScope>,
+    session: State<'_, ContextSession>,
+) -> CommandResult<CommitSuggestion> {
+    let (_, repository) = open_in_scope(&scope, &repo)?;
+    draft(&repository, &session.id())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn staged(status: Delta, insertions: usize, deletions: usize, added: &[&str]) -> StagedFile {
+        StagedFile {
+            status: Some(status),
+            insertions,
+            deletions,
+            added_symbols: added.iter().map(|s| s.to_string()).collect(),
+            removed_symbols: BTreeSet::new(),
+        }
+    }
+
+    fn files(entries: Vec<(&str, StagedFile)>) -> BTreeMap<String, StagedFile> {
+        entries
+            .into_iter()
+            .map(|(path, file)| (path.to_string(), file))
+            .collect()
+    }
+
+    #[test]
+    fn test_definition_from_code_lines() {
+        assert_eq!(
+            definition("pub(crate) fn parse(input: &str)").as_deref(),
+            Some("parse")
+        );
+        assert_eq!(
+            definition("export default class Foo {").as_deref(),
+            Some("Foo")
+        );
+        assert_eq!(
+            definition("    async def run(self):").as_deref(),
+            Some("run")
+        );
+        assert_eq!(definition("let total = 0;"), None);
+    }
+
+    #[test]
+    fn test_definition_ignores_comments_and_prose() {
+        assert_eq!(definition("// the fn parse is gone"), None);
+        assert_eq!(definition("# def run(): removed"), None);
+        assert_eq!(definition(" * @param type mod function"), None);
+        assert_eq!(definition("\"class Foo\""), None);
+        assert_eq!(definition("Call the function parse first."), None);
+        assert_eq!(definition("type Alias = string;"), None);
+    }
+
+    #[test]
+    fn test_infer_type_from_paths() {
+        let tests = files(vec![
+            ("tests/app.rs", staged(Delta::Modified, 5, 1, &[])),
+            ("src/queue.test.ts", staged(Delta::Modified, 2, 0, &[])),
+        ]);
+        assert_eq!(infer_type(&tests), ("test", true));
+        let docs = files(vec![("README.md", staged(Delta::Modified, 40, 3, &[]))]);
+        assert_eq!(infer_type(&docs), ("docs", true));
+        let build = files(vec![("Cargo.toml", staged(Delta::Modified, 1, 1, &[]))]);
+        assert_eq!(infer_type(&build), ("build", true));
+    }
+
+    #[test]
+    fn test_infer_type_from_changes() {
+        let new = files(vec![("src/inbox.rs", staged(Delta::Added, 80, 0, &[]))]);
+        assert_eq!(infer_type(&new), ("feat", true));
+        let symbol = files(vec![(
+            "src/inbox.rs",
+            staged(Delta::Modified, 30, 2, &["clear"]),
+        )]);
+        assert_eq!(infer_type(&symbol), ("feat", false));
+        let small = files(vec![("src/inbox.rs", staged(Delta::Modified, 3, 2, &[]))]);
+        assert_eq!(infer_type(&small), ("fix", false));
+        let removal = files(vec![("src/inbox.rs", staged(Delta::Modified, 4, 30, &[]))]);
+        assert_eq!(infer_type(&removal), ("refactor", false));
+        let large = files(vec![("src/inbox.rs", staged(Delta::Modified, 50, 40, &[]))]);
+        assert_eq!(infer_type(&large), ("chore", false));
+    }
+
+    #[test]
+    fn test_infer_scope() {
+        assert_eq!(
+            infer_scope(&["src-tauri/src/commands/queue.rs"]).as_deref(),
+            Some("queue")
+        );
+        assert_eq!(infer_scope(&["src/commands/mod.rs"]), None);
+        assert_eq!(
+            infer_scope(&["src/components/ui/a.tsx", "src/components/ui/b.tsx"]).as_deref(),
+            Some("ui")
+        );
+        assert_eq!(
+            infer_scope(&["src-tauri/src/commands/a.rs", "src-tauri/src/main.rs"]).as_deref(),
+            Some("src-tauri")
+        );
+        assert_eq!(
+            infer_scope(&["apps/ccm-api/routes.py", "apps/web/main.ts"]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_truncate() {
+        let short = "fix(queue): retry uploads".to_string();
+        assert_eq!(truncate(short.clone()), short);
+        let long = format!("feat(inbox): add {}", "persisted notification ".repeat(5));
+        let cut = truncate(long);
+        assert_eq!(cut.chars().count(), MAX_HEADER);
+        assert!(cut.ends_with('…'));
+        assert!(!cut.trim_end_matches('…').ends_with(' '));
+    }
+}
@@ -0,0 +1,158 @@
+use super::queue::EventQueue;
+use super::tracking::Tracking;
+use super::tray::TrayModel;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, State};
+
+/// Mirrors `Agent` in `apps/ccm-api/models/events.py`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Agent {
+    File,
+    Editor,
+    Terminal,
+    Git,
+    Flow,
+    Ai,
+    Browser,
+    /// Input typed by the developer, e.g. quick-capture notes.
+    Developer,
+}
+
+/// Mirrors `EventType` in `apps/ccm-api/models/events.py`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventType {
+    FileCreated,
+    FileModified,
+    FileDeleted,
+    FileRenamed,
+    EditorFocus,
+    EditorBlur,
+    CursorMoved,
+    TextSelected,
+    CodeExecuted,
+    TestRun,
+    CommandExecuted,
+    TerminalOutput,
+    CommitCreated,
+    BranchSwitched,
+    MergeConflict,
+    FlowStart,
+    FlowEnd,
+    StuckState,
+    PromptGenerated,
+    AiSuggestion,
+    NoteCaptured,
+    /// A span where the developer paused tracking.
+    TrackingGap,
+}
+
+/// Wire format of `POST /context/events`; field names follow the API model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextEvent {
+    pub session_id: String,
+    pub agent: Agent,
+    #[serde(rename = "type")]
+    pub event_type: EventType,
+    pub payload: serde_json::Value,
+    pub timestamp: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<String>,
+    /// Idempotency key: the backend stores an event with a given id once,
+    /// however often it is sent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<String>,
+}
+
+impl ContextEvent {
+    pub fn new(
+        session_id: &str,
+        agent: Agent,
+        event_type: EventType,
+        payload: serde_json::Value,
+    ) -> Self {
+        ContextEvent {
+            session_id: session_id.to_string(),
+            agent,
+            event_type,
+            payload,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            user_id: None,
+            project_id: None,
+            event_id: None,
+        }
+    }
+}
+
+/// The session every natively captured event is attributed to. The frontend
+/// replaces the generated id with the dashboard's session once it loads.
+pub struct ContextSession {
+    session_id: Mutex<String>,
+    current_file: Mutex<Option<PathBuf>>,
+}
+
+impl ContextSession {
+    pub fn new() -> Self {
+        ContextSession {
+            session_id: Mutex::new(uuid::Uuid::new_v4().to_string()),
+            current_file: Mutex::new(None),
+        }
+    }
+
+    pub fn id(&self) -> String {
+        self.session_id.lock().unwrap().clone()
+    }
+
+    /// The file most recently created, modified or focused.
+    pub fn current_file(&self) -> Option<PathBuf> {
+        self.current_file.lock().unwrap().clone()
+    }
+
+    fn note_file(&self, event: &ContextEvent) {
+        let key = match event.event_type {
+            EventType::FileCreated | EventType::FileModified => "path",
+            EventType::FileRenamed => "to",
+            EventType::EditorFocus | EventType::CursorMoved => "file_path",
+            _ => return,
+        };
+        if let Some(path) = event.payload.get(key).and_then(|p| p.as_str()) {
+            *self.current_file.lock().unwrap() = Some(PathBuf::from(path));
+        }
+    }
+}
+
+/// Single sink for every native capture source: the event is persisted to the
+/// offline queue first and then mirrored to the dashboard. While tracking is
+/// paused events are dropped here, before anything is recorded.
+pub fn record_event(app: &AppHandle, event: ContextEvent) {
+    if app.state::<Tracking>().is_paused() {
+        return;
+    }
+    app.state::<ContextSession>().note_file(&event);
+    let tray = app.state::<TrayModel>();
+    if event.event_type == EventType::StuckState {
+        tray.update(app, |s| s.stuck = true);
+    } else if event.agent != Agent::Flow {
+        tray.note_activity(app);
+    }
+    if let Some(queue) = app.try_state::<Arc<EventQueue>>() {
+        queue.enqueue(event.clone());
+    }
+    let _ = app.emit_all("context-event", &event);
+}
+
+#[tauri::command]
+pub fn get_session_id(session: State<'_, ContextSession>) -> String {
+    session.id()
+}
+
+#[tauri::command]
+pub fn set_session_id(session_id: String, session: State<'_, ContextSession>) {
+    *session.session_id.lock().unwrap() = session_id;
+}
@@ -0,0 +1,213 @@
+use super::error::{CommandError, CommandResult};
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+use url::Url;
+
+pub const SCHEME: &str = "pulsedev";
+const MAX_ID_LEN: usize = 64;
+
+/// A validated `pulsedev://` link, emitted to the frontend as `deep-link`.
+///
+/// Links arrive as launch arguments, so the scheme is only registered on
+/// Linux, through the `MimeType` line of `pulsedev.desktop`. macOS delivers
+/// links as Apple Events and Windows needs a registry entry; neither is
+/// handled, and the bundle does not claim the scheme there.
+///
+/// - `pulsedev://join?code=AB12CD34`
+/// - `pulsedev://sprint/<sprint id>?team=<team id>`
+/// - `pulsedev://session/<session id>`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DeepLink {
+    Join {
+        code: String,
+    },
+    Sprint {
+        sprint_id: String,
+        team_id: Option<String>,
+    },
+    Session {
+        session_id: String,
+    },
+}
+
+fn invalid(url: &str, why: &str) -> CommandError {
+    CommandError::InvalidInput(format!("{}: {}", url, why))
+}
+
+fn query(url: &Url, key: &str) -> Option<String> {
+    url.query_pairs()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Invite codes are generated server-side from `A-Z0-9`; accept lowercase
+/// too since people retype them.
+fn invite_code(raw: &str, url: &str) -> CommandResult<String> {
+    let code = raw.to_ascii_uppercase();
+    if !(4..=32).contains(&code.len()) || !code.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(invalid(url, "malformed invite code"));
+    }
+    Ok(code)
+}
+
+fn identifier(raw: &str, url: &str) -> CommandResult<String> {
+    let valid = !raw.is_empty()
+        && raw.len() <= MAX_ID_LEN
+        && raw
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(invalid(url, "malformed identifier"));
+    }
+    Ok(raw.to_string())
+}
+
+/// The id given either as the first path segment or as `?id=`.
+fn target_id(url: &Url) -> Option<String> {
+    url.path_segments()
+        .and_then(|mut segments| segments.next().map(str::to_string))
+        .filter(|s| !s.is_empty())
+        .or_else(|| query(url, "id"))
+}
+
+pub fn parse_deep_link(raw: &str) -> CommandResult<DeepLink> {
+    let url = Url::parse(raw.trim()).map_err(|e| invalid(raw, &e.to_string()))?;
+    if url.scheme() != SCHEME {
+        return Err(invalid(raw, "not a pulsedev:// link"));
+    }
+    match url.host_str() {
+        Some("join") => {
+            let code = query(&url, "code").ok_or_else(|| invalid(raw, "missing code"))?;
+            Ok(DeepLink::Join {
+                code: invite_code(&code, raw)?,
+            })
+        }
+        Some("sprint") => {
+            let id = target_id(&url).ok_or_else(|| invalid(raw, "missing sprint id"))?;
+            Ok(DeepLink::Sprint {
+                sprint_id: identifier(&id, raw)?,
+                team_id: query(&url, "team")
+                    .map(|t| identifier(&t, raw))
+                    .transpose()?,
+            })
+        }
+        Some("session") => {
+            let id = target_id(&url).ok_or_else(|| invalid(raw, "missing session id"))?;
+            Ok(DeepLink::Session {
+                session_id: identifier(&id, raw)?,
+            })
+        }
+        _ => Err(invalid(raw, "unknown link")),
+    }
+}
+
+/// The last link received, kept until the frontend asks for it so a link
+/// that launched the app is not lost before the webview has loaded.
+#[derive(Default)]
+pub struct PendingDeepLink(Mutex<Option<DeepLink>>);
+
+/// Handles any `pulsedev://` links among launch arguments, whether from this
+/// process or forwarded by a second launch.
+pub fn handle_deep_link_args(app: &AppHandle, args: &[String]) {
+    let prefix = format!("{}://", SCHEME);
+    for arg in args.iter().filter(|a| a.starts_with(&prefix)) {
+        match parse_deep_link(arg) {
+            Ok(link) => {
+                *app.state::<PendingDeepLink>().0.lock().unwrap() = Some(link.clone());
+                let _ = app.emit_all("deep-link", &link);
+            }
+            Err(e) => {
+                let _ = app.emit_all("deep-link-error", e.to_string());
+            }
+        }
+    }
+}
+
+/// Returns and clears the link that has not been handled yet.
+#[tauri::command]
+pub fn take_deep_link(pending: State<'_, PendingDeepLink>) -> Option<DeepLink> {
+    pending.0.lock().unwrap().take()
+}
+
+#[tauri::command]
+pub fn parse_link(url: String) -> CommandResult<DeepLink> {
+    parse_deep_link(&url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rejects(url: &str) -> bool {
+        matches!(parse_deep_link(url), Err(CommandError::InvalidInput(_)))
+    }
+
+    #[test]
+    fn test_parse_join_link() {
+        assert_eq!(
+            parse_deep_link("pulsedev://join?code=ab12cd34").unwrap(),
+            DeepLink::Join {
+                code: "AB12CD34".into()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_sprint_link() {
+        assert_eq!(
+            parse_deep_link("pulsedev://sprint/sprint_42?team=team-7").unwrap(),
+            DeepLink::Sprint {
+                sprint_id: "sprint_42".into(),
+                team_id: Some("team-7".into()),
+            }
+        );
+        assert_eq!(
+            parse_deep_link("pulsedev://sprint?id=sprint_42").unwrap(),
+            DeepLink::Sprint {
+                sprint_id: "sprint_42".into(),
+                team_id: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_session_link() {
+        assert_eq!(
+            parse_deep_link("pulsedev://session/ccm_session_1700000000").unwrap(),
+            DeepLink::Session {
+                session_id: "ccm_session_1700000000".into()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_other_schemes() {
+        assert!(rejects("https://join?code=AB12CD34"));
+        assert!(rejects("not a url"));
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_hosts() {
+        assert!(rejects("pulsedev://settings?code=AB12CD34"));
+        assert!(rejects("pulsedev:join?code=AB12CD34"));
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_codes() {
+        assert!(rejects("pulsedev://join"));
+        assert!(rejects("pulsedev://join?code=AB1"));
+        assert!(rejects("pulsedev://join?code=AB12-CD34"));
+        assert!(rejects(&format!("pulsedev://join?code={}", "A".repeat(33))));
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_identifiers() {
+        assert!(rejects("pulsedev://session"));
+        assert!(rejects("pulsedev://session/abc%2F..%2Fetc"));
+        assert!(rejects("pulsedev://sprint/s1?team=a%20b"));
+        assert!(rejects(&format!("pulsedev://session/{}", "a".repeat(65))));
+    }
+}
@@ -1,100 +1,322 @@
-use tauri::api::log::LogTarget;
+use super::context::{record_event, Agent, ContextEvent, ContextSession, EventType};
+use super::error::{CommandError, CommandResult};
+use super::policy::deliver_digest;
+use super::tray::TrayModel;
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
 
-#[tauri::command]
-pub fn get_dnd_status() -> bool {
-    #[cfg(target_os = "macos")]
-    {
-        // macOS: Use AppleScript to check DND status (macOS 12+)
-        use std::process::Command;
-        let output = Command::new("defaults")
-            .arg("-currentHost")
-            .arg("read")
-            .arg("com.apple.notificationcenterui")
-            .arg("doNotDisturb")
-            .output();
-        if let Ok(out) = output {
-            let stdout = String::from_utf8_lossy(&out.stdout);
-            return stdout.trim() == "1";
-        }
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A desktop's notification-suppression switch. Backends are picked once at
+/// startup by `detect_backend`; `PULSEDEV_DND_BACKEND` overrides detection.
+pub trait DndBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn is_enabled(&self) -> CommandResult<bool>;
+    fn set_enabled(&self, enabled: bool) -> CommandResult<()>;
+
+    /// Blocks, calling `on_change` whenever the switch may have flipped, and
+    /// returns `true` once the subscription ends. Backends without change
+    /// notification return `false` immediately and are polled instead.
+    fn watch(&self, _on_change: &dyn Fn()) -> bool {
         false
     }
-    #[cfg(target_os = "windows")]
-    {
-        // Windows: Query Focus Assist (Quiet Hours) from registry
-        use std::process::Command;
-        let output = Command::new("reg")
-            .args([
-                "query",
-                "HKCU\\SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\Notifications\\Settings",
-                "/v",
-                "NOC_GLOBAL_SETTING_TOASTS_ENABLED",
-            ])
-            .output();
-        if let Ok(out) = output {
-            let stdout = String::from_utf8_lossy(&out.stdout);
-            // If value is 0, DND is ON; if 1, DND is OFF
-            if stdout.contains("0x0") {
-                return true;
+}
+
+fn run(program: &str, args: &[&str]) -> CommandResult<String> {
+    let out = Command::new(program).args(args).output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            CommandError::Unsupported(format!("{} is not installed", program))
+        } else {
+            CommandError::from(e)
+        }
+    })?;
+    if !out.status.success() {
+        return Err(CommandError::BackendUnavailable(format!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&out.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+fn has_program(program: &str) -> bool {
+    Command::new("which")
+        .arg(program)
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// GNOME: DND is the inverse of `org.gnome.desktop.notifications show-banners`.
+pub struct GnomeBackend;
+
+impl DndBackend for GnomeBackend {
+    fn name(&self) -> &'static str {
+        "gnome"
+    }
+
+    fn is_enabled(&self) -> CommandResult<bool> {
+        let out = run(
+            "gsettings",
+            &["get", "org.gnome.desktop.notifications", "show-banners"],
+        )?;
+        Ok(out == "false")
+    }
+
+    fn set_enabled(&self, enabled: bool) -> CommandResult<()> {
+        let value = if enabled { "false" } else { "true" };
+        run(
+            "gsettings",
+            &[
+                "set",
+                "org.gnome.desktop.notifications",
+                "show-banners",
+                value,
+            ],
+        )
+        .map(|_| ())
+    }
+
+    fn watch(&self, on_change: &dyn Fn()) -> bool {
+        let child = Command::new("gsettings")
+            .args(["monitor", "org.gnome.desktop.notifications", "show-banners"])
+            .stdout(Stdio::piped())
+            .spawn();
+        let Ok(mut child) = child else {
+            return false;
+        };
+        if let Some(stdout) = child.stdout.take() {
+            for _ in BufReader::new(stdout).lines().map_while(Result::ok) {
+                on_change();
             }
         }
-        false
+        let _ = child.wait();
+        true
+    }
+}
+
+/// KDE Plasma: `org.freedesktop.Notifications.Inhibit`. The inhibition lives
+/// as long as the D-Bus connection that requested it, so the connection and
+/// cookie are held here rather than shelling out to `gdbus`.
+pub struct KdeBackend {
+    connection: zbus::blocking::Connection,
+    cookie: Mutex<Option<u32>>,
+}
+
+impl KdeBackend {
+    const DEST: &'static str = "org.freedesktop.Notifications";
+    const PATH: &'static str = "/org/freedesktop/Notifications";
+
+    pub fn connect() -> CommandResult<Self> {
+        let connection = zbus::blocking::Connection::session()?;
+        Ok(KdeBackend {
+            connection,
+            cookie: Mutex::new(None),
+        })
+    }
+
+    fn proxy(&self) -> CommandResult<zbus::blocking::Proxy<'_>> {
+        Ok(zbus::blocking::Proxy::new(
+            &self.connection,
+            Self::DEST,
+            Self::PATH,
+            Self::DEST,
+        )?)
+    }
+}
+
+impl DndBackend for KdeBackend {
+    fn name(&self) -> &'static str {
+        "kde"
+    }
+
+    fn is_enabled(&self) -> CommandResult<bool> {
+        if self.cookie.lock().unwrap().is_some() {
+            return Ok(true);
+        }
+        Ok(self.proxy()?.get_property::<bool>("Inhibited")?)
     }
-    #[cfg(target_os = "linux")]
-    {
-        // Linux: Query D-Bus for org.gnome.SessionManager (GNOME)
-        use std::process::Command;
-        let output = Command::new("gdbus")
-            .args([
-                "call",
-                "--session",
-                "--dest",
-                "org.gnome.SessionManager",
-                "--object-path",
-                "/org/gnome/SessionManager/Presence",
-                "--method",
-                "org.gnome.SessionManager.Presence.GetStatus",
-            ])
-            .output();
-        if let Ok(out) = output {
-            let stdout = String::from_utf8_lossy(&out.stdout);
-            // 0 = available, 2 = DND
-            if stdout.contains("2") {
-                return true;
+
+    fn set_enabled(&self, enabled: bool) -> CommandResult<()> {
+        let mut cookie = self.cookie.lock().unwrap();
+        let proxy = self.proxy()?;
+        match (enabled, *cookie) {
+            (true, None) => {
+                let hints: std::collections::HashMap<&str, zbus::zvariant::Value> =
+                    std::collections::HashMap::new();
+                let id: u32 = proxy.call("Inhibit", &("dev.pulse.ccm", "Focus session", hints))?;
+                *cookie = Some(id);
             }
+            (false, Some(id)) => {
+                proxy.call::<_, _, ()>("UnInhibit", &(id,))?;
+                *cookie = None;
+            }
+            _ => {}
         }
-        false
+        Ok(())
     }
-    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
-    {
-        false
+
+    fn watch(&self, on_change: &dyn Fn()) -> bool {
+        let Ok(proxy) = self.proxy() else {
+            return false;
+        };
+        for _ in proxy.receive_property_changed::<bool>("Inhibited") {
+            on_change();
+        }
+        true
     }
 }
 
-#[tauri::command]
-pub fn set_dnd_status(enabled: bool) -> bool {
-    #[cfg(target_os = "macos")]
-    {
-        // macOS: Use AppleScript to toggle DND (macOS 12+)
-        use std::process::Command;
+/// XFCE: `xfce4-notifyd` reads `/do-not-disturb` from xfconf.
+pub struct XfceBackend;
+
+impl DndBackend for XfceBackend {
+    fn name(&self) -> &'static str {
+        "xfce"
+    }
+
+    fn is_enabled(&self) -> CommandResult<bool> {
+        let out = run(
+            "xfconf-query",
+            &["-c", "xfce4-notifyd", "-p", "/do-not-disturb"],
+        )?;
+        Ok(out == "true")
+    }
+
+    fn set_enabled(&self, enabled: bool) -> CommandResult<()> {
+        let value = if enabled { "true" } else { "false" };
+        run(
+            "xfconf-query",
+            &[
+                "-c",
+                "xfce4-notifyd",
+                "-p",
+                "/do-not-disturb",
+                "--create",
+                "-t",
+                "bool",
+                "-s",
+                value,
+            ],
+        )
+        .map(|_| ())
+    }
+}
+
+/// mako (Sway and other wlroots compositors): a `do-not-disturb` mode that
+/// the user's mako config is expected to define with `invisible=1`.
+pub struct MakoBackend;
+
+impl DndBackend for MakoBackend {
+    fn name(&self) -> &'static str {
+        "mako"
+    }
+
+    fn is_enabled(&self) -> CommandResult<bool> {
+        let out = run("makoctl", &["mode"])?;
+        Ok(out.lines().any(|mode| mode.trim() == "do-not-disturb"))
+    }
+
+    fn set_enabled(&self, enabled: bool) -> CommandResult<()> {
+        let flag = if enabled { "-a" } else { "-r" };
+        run("makoctl", &["mode", flag, "do-not-disturb"]).map(|_| ())
+    }
+}
+
+/// dunst: pausing holds notifications until resumed.
+pub struct DunstBackend;
+
+impl DndBackend for DunstBackend {
+    fn name(&self) -> &'static str {
+        "dunst"
+    }
+
+    fn is_enabled(&self) -> CommandResult<bool> {
+        Ok(run("dunstctl", &["is-paused"])? == "true")
+    }
+
+    fn set_enabled(&self, enabled: bool) -> CommandResult<()> {
+        let value = if enabled { "true" } else { "false" };
+        run("dunstctl", &["set-paused", value]).map(|_| ())
+    }
+}
+
+/// macOS: `com.apple.notificationcenterui doNotDisturb` (macOS 12+).
+pub struct MacBackend;
+
+impl DndBackend for MacBackend {
+    fn name(&self) -> &'static str {
+        "macos"
+    }
+
+    fn is_enabled(&self) -> CommandResult<bool> {
+        let out = run(
+            "defaults",
+            &[
+                "-currentHost",
+                "read",
+                "com.apple.notificationcenterui",
+                "doNotDisturb",
+            ],
+        )?;
+        Ok(out == "1")
+    }
+
+    fn set_enabled(&self, enabled: bool) -> CommandResult<()> {
         let value = if enabled { "1" } else { "0" };
-        let output = Command::new("defaults")
-            .arg("-currentHost")
-            .arg("write")
-            .arg("com.apple.notificationcenterui")
-            .arg("doNotDisturb")
-            .arg(value)
-            .output();
-        return output.map(|o| o.status.success()).unwrap_or(false);
-    }
-    #[cfg(target_os = "windows")]
-    {
-        // Windows: Set Focus Assist (Quiet Hours) via registry
-        use std::process::Command;
+        run(
+            "defaults",
+            &[
+                "-currentHost",
+                "write",
+                "com.apple.notificationcenterui",
+                "doNotDisturb",
+                value,
+            ],
+        )
+        .map(|_| ())
+    }
+}
+
+/// Windows: Focus Assist (Quiet Hours) via the toast registry switch.
+pub struct WindowsBackend;
+
+impl WindowsBackend {
+    const KEY: &'static str =
+        "HKCU\\SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\Notifications\\Settings";
+}
+
+impl DndBackend for WindowsBackend {
+    fn name(&self) -> &'static str {
+        "windows"
+    }
+
+    fn is_enabled(&self) -> CommandResult<bool> {
+        let out = run(
+            "reg",
+            &[
+                "query",
+                Self::KEY,
+                "/v",
+                "NOC_GLOBAL_SETTING_TOASTS_ENABLED",
+            ],
+        )?;
+        // If value is 0, DND is ON; if 1, DND is OFF
+        Ok(out.contains("0x0"))
+    }
+
+    fn set_enabled(&self, enabled: bool) -> CommandResult<()> {
         let value = if enabled { "0" } else { "1" };
-        let output = Command::new("reg")
-            .args([
+        run(
+            "reg",
+            &[
                 "add",
-                "HKCU\\SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\Notifications\\Settings",
+                Self::KEY,
                 "/v",
                 "NOC_GLOBAL_SETTING_TOASTS_ENABLED",
                 "/t",
@@ -102,32 +324,280 @@ pub fn set_dnd_status(enabled: bool) -> bool {
                 "/d",
                 value,
                 "/f",
-            ])
-            .output();
-        return output.map(|o| o.status.success()).unwrap_or(false);
-    }
-    #[cfg(target_os = "linux")]
-    {
-        // Linux: Set DND via D-Bus (GNOME)
-        use std::process::Command;
-        let value = if enabled { "2" } else { "0" };
-        let output = Command::new("gdbus")
-            .args([
-                "call",
-                "--session",
-                "--dest",
-                "org.gnome.SessionManager",
-                "--object-path",
-                "/org/gnome/SessionManager/Presence",
-                "--method",
-                "org.gnome.SessionManager.Presence.SetStatus",
-                value,
-            ])
-            .output();
-        return output.map(|o| o.status.success()).unwrap_or(false);
+            ],
+        )
+        .map(|_| ())
     }
-    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
-    {
-        false
+}
+
+/// In-memory backend for tests and for desktops without a supported switch
+/// when `PULSEDEV_DND_BACKEND=fake` is set.
+#[derive(Default)]
+pub struct FakeBackend {
+    enabled: Mutex<bool>,
+}
+
+impl DndBackend for FakeBackend {
+    fn name(&self) -> &'static str {
+        "fake"
+    }
+
+    fn is_enabled(&self) -> CommandResult<bool> {
+        Ok(*self.enabled.lock().unwrap())
+    }
+
+    fn set_enabled(&self, enabled: bool) -> CommandResult<()> {
+        *self.enabled.lock().unwrap() = enabled;
+        Ok(())
+    }
+}
+
+fn backend_by_name(name: &str) -> Option<Box<dyn DndBackend>> {
+    match name {
+        "gnome" => Some(Box::new(GnomeBackend)),
+        "kde" => KdeBackend::connect()
+            .ok()
+            .map(|b| Box::new(b) as Box<dyn DndBackend>),
+        "xfce" => Some(Box::new(XfceBackend)),
+        "mako" => Some(Box::new(MakoBackend)),
+        "dunst" => Some(Box::new(DunstBackend)),
+        "macos" => Some(Box::new(MacBackend)),
+        "windows" => Some(Box::new(WindowsBackend)),
+        "fake" => Some(Box::new(FakeBackend::default())),
+        _ => None,
+    }
+}
+
+/// Backend names to try, in order, for the given override, OS and
+/// `XDG_CURRENT_DESKTOP`. On Linux the desktop environment wins over
+/// standalone daemons, since GNOME, KDE and XFCE ship their own
+/// notification servers.
+fn backend_candidates(override_name: Option<&str>, os: &str, desktop: &str) -> Vec<String> {
+    if let Some(name) = override_name {
+        return vec![name.to_lowercase()];
+    }
+    match os {
+        "macos" | "windows" => return vec![os.to_string()],
+        _ => {}
+    }
+    let desktop = desktop.to_uppercase();
+    let by_desktop = if desktop.contains("KDE") {
+        Some("kde")
+    } else if desktop.contains("GNOME") || desktop.contains("UNITY") {
+        Some("gnome")
+    } else if desktop.contains("XFCE") {
+        Some("xfce")
+    } else {
+        None
+    };
+    by_desktop
+        .into_iter()
+        .chain(["mako", "dunst"])
+        .map(str::to_string)
+        .collect()
+}
+
+/// Picks the backend for the running desktop. `PULSEDEV_DND_BACKEND`
+/// overrides detection; standalone daemons are only used when installed.
+pub fn detect_backend() -> Option<Box<dyn DndBackend>> {
+    let override_name = std::env::var("PULSEDEV_DND_BACKEND").ok();
+    let desktop = std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
+    let candidates = backend_candidates(override_name.as_deref(), std::env::consts::OS, &desktop);
+    if override_name.is_some() {
+        return backend_by_name(&candidates[0]);
+    }
+    candidates
+        .iter()
+        .filter(|name| {
+            !matches!(name.as_str(), "mako" | "dunst") || has_program(&format!("{}ctl", name))
+        })
+        .find_map(|name| backend_by_name(name))
+}
+
+/// Managed state wrapping the detected backend, if any.
+pub struct Dnd {
+    backend: Option<Box<dyn DndBackend>>,
+}
+
+impl Dnd {
+    pub fn detect() -> Self {
+        Dnd {
+            backend: detect_backend(),
+        }
+    }
+
+    pub fn backend_name(&self) -> Option<&'static str> {
+        self.backend.as_ref().map(|b| b.name())
+    }
+
+    pub fn is_enabled(&self) -> CommandResult<bool> {
+        match &self.backend {
+            Some(backend) => backend.is_enabled(),
+            None => Err(CommandError::Unsupported(
+                "No supported Do Not Disturb backend".to_string(),
+            )),
+        }
+    }
+
+    pub fn set_enabled(&self, enabled: bool) -> CommandResult<()> {
+        match &self.backend {
+            Some(backend) => backend.set_enabled(enabled),
+            None => Err(CommandError::Unsupported(
+                "No supported Do Not Disturb backend".to_string(),
+            )),
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_dnd_status(dnd: State<'_, Dnd>) -> CommandResult<bool> {
+    dnd.is_enabled()
+}
+
+#[tauri::command]
+pub fn set_dnd_status(enabled: bool, dnd: State<'_, Dnd>) -> CommandResult<()> {
+    dnd.set_enabled(enabled)
+}
+
+#[tauri::command]
+pub fn get_dnd_backend(dnd: State<'_, Dnd>) -> Option<String> {
+    dnd.backend_name().map(String::from)
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct DndChanged {
+    pub enabled: bool,
+    pub backend: &'static str,
+}
+
+/// Watches the DND switch for changes made inside or outside the app. Each
+/// change is pushed as `dnd-changed` and recorded as a `flow_start` /
+/// `flow_end` context event so analytics can see when focus began.
+pub fn spawn_dnd_watcher(app: AppHandle) {
+    thread::spawn(move || {
+        let dnd = app.state::<Dnd>();
+        let Some(backend) = dnd.backend.as_deref() else {
+            return;
+        };
+        let initial = backend.is_enabled().ok();
+        if let Some(enabled) = initial {
+            app.state::<TrayModel>().update(&app, |s| s.dnd = enabled);
+        }
+        let last = Mutex::new(initial);
+        let check = || {
+            let Ok(enabled) = backend.is_enabled() else {
+                return;
+            };
+            let mut last = last.lock().unwrap();
+            if *last == Some(enabled) {
+                return;
+            }
+            *last = Some(enabled);
+            notify_change(&app, enabled, backend.name());
+        };
+        // A subscription that ends (daemon restarted, bus dropped) is retried
+        // after the same delay as a poll.
+        loop {
+            if !backend.watch(&check) {
+                check();
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+fn notify_change(app: &AppHandle, enabled: bool, backend: &'static str) {
+    let _ = app.emit_all("dnd-changed", DndChanged { enabled, backend });
+    app.state::<TrayModel>().update(app, |s| s.dnd = enabled);
+    if !enabled {
+        let _ = deliver_digest(app);
+    }
+    let event_type = if enabled {
+        EventType::FlowStart
+    } else {
+        EventType::FlowEnd
+    };
+    let session_id = app.state::<ContextSession>().id();
+    record_event(
+        app,
+        ContextEvent::new(
+            &session_id,
+            Agent::Flow,
+            event_type,
+            serde_json::json!({ "source": "dnd", "backend": backend, "dnd": enabled }),
+        ),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fake_dnd() -> Dnd {
+        Dnd {
+            backend: backend_by_name("fake"),
+        }
+    }
+
+    #[test]
+    fn test_candidates_honour_override() {
+        assert_eq!(backend_candidates(Some("Fake"), "linux", "KDE"), ["fake"]);
+        assert_eq!(backend_candidates(Some("dunst"), "macos", ""), ["dunst"]);
+    }
+
+    #[test]
+    fn test_candidates_by_os() {
+        assert_eq!(backend_candidates(None, "macos", "GNOME"), ["macos"]);
+        assert_eq!(backend_candidates(None, "windows", ""), ["windows"]);
+    }
+
+    #[test]
+    fn test_candidates_prefer_desktop_over_daemons() {
+        assert_eq!(
+            backend_candidates(None, "linux", "KDE"),
+            ["kde", "mako", "dunst"]
+        );
+        assert_eq!(
+            backend_candidates(None, "linux", "ubuntu:GNOME"),
+            ["gnome", "mako", "dunst"]
+        );
+        assert_eq!(
+            backend_candidates(None, "linux", "xfce"),
+            ["xfce", "mako", "dunst"]
+        );
+        assert_eq!(backend_candidates(None, "linux", "sway"), ["mako", "dunst"]);
+    }
+
+    #[test]
+    fn test_backend_by_name() {
+        for name in ["gnome", "xfce", "mako", "dunst", "macos", "windows", "fake"] {
+            assert_eq!(backend_by_name(name).map(|b| b.name()), Some(name));
+        }
+        assert!(backend_by_name("plasma").is_none());
+    }
+
+    #[test]
+    fn test_fake_round_trip() {
+        let dnd = fake_dnd();
+        assert_eq!(dnd.backend_name(), Some("fake"));
+        assert!(!dnd.is_enabled().unwrap());
+        dnd.set_enabled(true).unwrap();
+        assert!(dnd.is_enabled().unwrap());
+        dnd.set_enabled(false).unwrap();
+        assert!(!dnd.is_enabled().unwrap());
+    }
+
+    #[test]
+    fn test_without_backend_is_unsupported() {
+        let dnd = Dnd { backend: None };
+        assert_eq!(dnd.backend_name(), None);
+        assert!(matches!(
+            dnd.is_enabled(),
+            Err(CommandError::Unsupported(_))
+        ));
+        assert!(matches!(
+            dnd.set_enabled(true),
+            Err(CommandError::Unsupported(_))
+        ));
     }
 }
@@ -0,0 +1,102 @@
+use super::api::ApiError;
+use serde::Serialize;
+
+/// Error returned by every command. It serializes as
+/// `{ "code": "OutOfScope", "message": "..." }`; the codes are part of the
+/// frontend contract and must not be renamed.
+#[derive(Debug, Serialize)]
+#[serde(tag = "code", content = "message")]
+pub enum CommandError {
+    NotFound(String),
+    PermissionDenied(String),
+    OutOfScope(String),
+    InvalidInput(String),
+    BackendUnavailable(String),
+    Unsupported(String),
+    /// Something outside the app already holds what was asked for, e.g. a
+    /// global shortcut another application registered.
+    Conflict(String),
+    Io(String),
+}
+
+pub type CommandResult<T> = Result<T, CommandError>;
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandError::NotFound(m) => write!(f, "Not found: {}", m),
+            CommandError::PermissionDenied(m) => write!(f, "Permission denied: {}", m),
+            CommandError::OutOfScope(m) => {
+                write!(f, "Path is outside the granted workspaces: {}", m)
+            }
+            CommandError::InvalidInput(m) => write!(f, "Invalid input: {}", m),
+            CommandError::BackendUnavailable(m) => write!(f, "Backend unavailable: {}", m),
+            CommandError::Unsupported(m) => write!(f, "Unsupported: {}", m),
+            CommandError::Conflict(m) => write!(f, "Conflict: {}", m),
+            CommandError::Io(m) => write!(f, "IO error: {}", m),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl From<std::io::Error> for CommandError {
+    fn from(e: std::io::Error) -> Self {
+        match e.kind() {
+            std::io::ErrorKind::NotFound => CommandError::NotFound(e.to_string()),
+            std::io::ErrorKind::PermissionDenied => CommandError::PermissionDenied(e.to_string()),
+            _ => CommandError::Io(e.to_string()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for CommandError {
+    fn from(e: serde_json::Error) -> Self {
+        CommandError::Io(e.to_string())
+    }
+}
+
+impl From<tauri::Error> for CommandError {
+    fn from(e: tauri::Error) -> Self {
+        CommandError::BackendUnavailable(e.to_string())
+    }
+}
+
+impl From<zbus::Error> for CommandError {
+    fn from(e: zbus::Error) -> Self {
+        CommandError::BackendUnavailable(format!("D-Bus: {}", e))
+    }
+}
+
+impl From<notify::Error> for CommandError {
+    fn from(e: notify::Error) -> Self {
+        let message = e.to_string();
+        match e.kind {
+            notify::ErrorKind::Io(io) => io.into(),
+            notify::ErrorKind::PathNotFound => CommandError::NotFound(message),
+            notify::ErrorKind::MaxFilesWatch => CommandError::Unsupported(message),
+            _ => CommandError::BackendUnavailable(message),
+        }
+    }
+}
+
+impl From<git2::Error> for CommandError {
+    fn from(e: git2::Error) -> Self {
+        match e.code() {
+            git2::ErrorCode::NotFound => CommandError::NotFound(e.message().to_string()),
+            _ => CommandError::BackendUnavailable(format!("git: {}", e.message())),
+        }
+    }
+}
+
+impl From<ApiError> for CommandError {
+    fn from(e: ApiError) -> Self {
+        match e {
+            ApiError::Status { code: 401, .. } | ApiError::Status { code: 403, .. } => {
+                CommandError::PermissionDenied(e.to_string())
+            }
+            ApiError::Status { code: 404, .. } => CommandError::NotFound(e.to_string()),
+            _ => CommandError::BackendUnavailable(e.to_string()),
+        }
+    }
+}
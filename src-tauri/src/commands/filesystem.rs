@@ -1,25 +1,29 @@
+use super::error::CommandResult;
+use super::scope::WorkspaceScope;
 use std::fs;
-use tauri::{api::dialog::FileDialogBuilder, Window};
+use tauri::{api::dialog::blocking::FileDialogBuilder, State, Window};
 
-#[tauri::command]
-pub fn pick_file(window: Window) -> Option<String> {
-    let (tx, rx) = std::sync::mpsc::channel();
-    FileDialogBuilder::new().pick_file(move |file| {
-        if let Some(path) = file {
-            tx.send(Some(path.display().to_string())).ok();
-        } else {
-            tx.send(None).ok();
-        }
-    });
-    rx.recv().unwrap_or(None)
+/// Picking a file through the native dialog counts as an explicit grant for
+/// that single file, so it can be read back without granting its directory.
+/// Async for the same reason as `grant_workspace`.
+#[tauri::command(async)]
+pub fn pick_file(window: Window, scope: State<'_, WorkspaceScope>) -> Option<String> {
+    let path = FileDialogBuilder::new().set_parent(&window).pick_file()?;
+    scope.grant(&path).ok().map(|p| p.display().to_string())
 }
 
 #[tauri::command]
-pub fn read_file(path: String) -> Result<String, String> {
-    fs::read_to_string(&path).map_err(|e| format!("Read error: {}", e))
+pub fn read_file(path: String, scope: State<'_, WorkspaceScope>) -> CommandResult<String> {
+    let resolved = scope.resolve(&path)?;
+    Ok(fs::read_to_string(resolved)?)
 }
 
 #[tauri::command]
-pub fn write_file(path: String, contents: String) -> Result<(), String> {
-    fs::write(&path, contents).map_err(|e| format!("Write error: {}", e))
+pub fn write_file(
+    path: String,
+    contents: String,
+    scope: State<'_, WorkspaceScope>,
+) -> CommandResult<()> {
+    let resolved = scope.resolve(&path)?;
+    Ok(fs::write(resolved, contents)?)
 }
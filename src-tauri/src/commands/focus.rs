@@ -0,0 +1,239 @@
+use super::dnd::Dnd;
+use super::error::{CommandError, CommandResult};
+use super::policy::deliver_digest;
+use super::store::JsonStore;
+use super::tray::TrayModel;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+const FOCUS_FILE: &str = "focus_session.json";
+const TICK: Duration = Duration::from_secs(1);
+/// Length of sessions started from the tray or a shortcut.
+const QUICK_FOCUS_SECS: u64 = 25 * 60;
+const MAX_FOCUS_SECS: u64 = 24 * 60 * 60;
+
+/// A running focus session. It is written to disk as soon as DND is turned
+/// on so that a crash still leaves enough behind to put DND back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusSession {
+    pub id: String,
+    pub reason: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    /// DND state before the session started; restored when it ends.
+    pub previous_dnd: bool,
+}
+
+impl FocusSession {
+    pub fn remaining_secs(&self) -> i64 {
+        (self.ends_at - Utc::now()).num_seconds().max(0)
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct FocusTick {
+    pub id: String,
+    pub remaining_secs: i64,
+}
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FocusEndReason {
+    Expired,
+    Cancelled,
+    AppExit,
+}
+
+#[derive(Clone, Serialize)]
+pub struct FocusEnded {
+    pub id: String,
+    pub reason: FocusEndReason,
+}
+
+pub struct Focus {
+    session: Mutex<Option<FocusSession>>,
+    store: JsonStore,
+}
+
+impl Focus {
+    pub fn load(data_dir: Option<PathBuf>) -> Self {
+        let store = JsonStore::new(data_dir.as_deref(), FOCUS_FILE);
+        let session = store.load();
+        Focus {
+            session: Mutex::new(session),
+            store,
+        }
+    }
+
+    pub fn current(&self) -> Option<FocusSession> {
+        self.session.lock().unwrap().clone()
+    }
+
+    fn persist(&self, session: Option<&FocusSession>) -> CommandResult<()> {
+        match session {
+            Some(session) => self.store.save(session)?,
+            None => self.store.remove()?,
+        }
+        Ok(())
+    }
+
+    /// Ends the session with `id` (or whichever is running, if `None`) and
+    /// puts DND back the way it was. Returns the ended session, if any.
+    pub fn end(
+        &self,
+        app: &AppHandle,
+        id: Option<&str>,
+        reason: FocusEndReason,
+    ) -> Option<FocusSession> {
+        let mut current = self.session.lock().unwrap();
+        if id.is_some_and(|id| current.as_ref().map(|s| s.id.as_str()) != Some(id)) {
+            return None;
+        }
+        let session = current.take()?;
+        let _ = app.state::<Dnd>().set_enabled(session.previous_dnd);
+        let _ = self.persist(None);
+        drop(current);
+
+        let _ = app.emit_all(
+            "focus-ended",
+            FocusEnded {
+                id: session.id.clone(),
+                reason,
+            },
+        );
+        update_tray(app, None);
+        if !matches!(reason, FocusEndReason::AppExit) {
+            let _ = deliver_digest(app);
+        }
+        Some(session)
+    }
+}
+
+/// Resumes a session left behind by the previous run: if it is still within
+/// its window DND is turned back on and the countdown restarts, otherwise DND
+/// is restored right away. Backends that inhibit notifications per D-Bus
+/// connection lose DND with the old process, so it is always re-applied.
+pub fn resume_focus(app: &AppHandle) {
+    let focus = app.state::<Focus>();
+    let Some(session) = focus.current() else {
+        return;
+    };
+    if session.remaining_secs() > 0 {
+        let _ = app.state::<Dnd>().set_enabled(true);
+        spawn_ticker(app.clone(), session.id);
+    } else {
+        focus.end(app, Some(&session.id), FocusEndReason::Expired);
+    }
+}
+
+fn update_tray(app: &AppHandle, remaining_secs: Option<i64>) {
+    app.state::<TrayModel>()
+        .update(app, |s| s.focus_remaining_secs = remaining_secs);
+}
+
+fn spawn_ticker(app: AppHandle, id: String) {
+    thread::spawn(move || loop {
+        let focus = app.state::<Focus>();
+        let remaining = match focus.current() {
+            Some(session) if session.id == id => session.remaining_secs(),
+            // Cancelled or replaced by a newer session.
+            _ => return,
+        };
+        if remaining == 0 {
+            focus.end(&app, Some(&id), FocusEndReason::Expired);
+            return;
+        }
+        let _ = app.emit_all(
+            "focus-tick",
+            FocusTick {
+                id: id.clone(),
+                remaining_secs: remaining,
+            },
+        );
+        update_tray(&app, Some(remaining));
+        thread::sleep(TICK);
+    });
+}
+
+/// Turns DND on for `duration` seconds, replacing any running session.
+/// Sessions last from one second to a day.
+pub fn start_focus(
+    app: &AppHandle,
+    duration: u64,
+    reason: Option<String>,
+) -> CommandResult<FocusSession> {
+    let started_at = Utc::now();
+    let ends_at = (1..=MAX_FOCUS_SECS)
+        .contains(&duration)
+        .then(|| started_at.checked_add_signed(ChronoDuration::seconds(duration as i64)))
+        .flatten()
+        .ok_or_else(|| {
+            CommandError::InvalidInput(format!(
+                "Focus sessions last between 1 and {} seconds",
+                MAX_FOCUS_SECS
+            ))
+        })?;
+    let dnd = app.state::<Dnd>();
+    let focus = app.state::<Focus>();
+    let mut current = focus.session.lock().unwrap();
+    // Starting over an existing session keeps the original pre-focus state.
+    let previous_dnd = match current.as_ref() {
+        Some(session) => session.previous_dnd,
+        None => dnd.is_enabled().unwrap_or(false),
+    };
+    let session = FocusSession {
+        id: uuid::Uuid::new_v4().to_string(),
+        reason,
+        started_at,
+        ends_at,
+        previous_dnd,
+    };
+    // Saved first, so that DND is never on without a record of how to turn
+    // it back off after a crash.
+    focus.persist(Some(&session))?;
+    if let Err(e) = dnd.set_enabled(true) {
+        let _ = focus.persist(current.as_ref());
+        return Err(e);
+    }
+    *current = Some(session.clone());
+    drop(current);
+
+    let _ = app.emit_all("focus-started", &session);
+    spawn_ticker(app.clone(), session.id.clone());
+    Ok(session)
+}
+
+/// Ends the running session, or starts a default-length one if none is
+/// running.
+pub fn toggle_focus(app: &AppHandle, reason: &str) {
+    let focus = app.state::<Focus>();
+    if focus.current().is_some() {
+        focus.end(app, None, FocusEndReason::Cancelled);
+    } else {
+        let _ = start_focus(app, QUICK_FOCUS_SECS, Some(reason.to_string()));
+    }
+}
+
+#[tauri::command]
+pub fn start_focus_session(
+    duration: u64,
+    reason: Option<String>,
+    app: AppHandle,
+) -> CommandResult<FocusSession> {
+    start_focus(&app, duration, reason)
+}
+
+#[tauri::command]
+pub fn cancel_focus_session(app: AppHandle, focus: State<'_, Focus>) -> bool {
+    focus.end(&app, None, FocusEndReason::Cancelled).is_some()
+}
+
+#[tauri::command]
+pub fn get_focus_session(focus: State<'_, Focus>) -> Option<FocusSession> {
+    focus.current()
+}
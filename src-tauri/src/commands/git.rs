@@ -0,0 +1,116 @@
+use super::error::{CommandError, CommandResult};
+use super::scope::WorkspaceScope;
+use git2::{Repository, RepositoryState};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// A repository located on disk: the working tree and its git directory,
+/// which differ for worktrees and submodules.
+#[derive(Debug, Clone)]
+pub struct Repo {
+    pub work_tree: PathBuf,
+    pub git_dir: PathBuf,
+}
+
+/// Walks up from `path` to the nearest repository.
+pub fn find_repo(path: &Path) -> Option<Repo> {
+    path.ancestors().find_map(|dir| {
+        let dot_git = dir.join(".git");
+        let git_dir = if dot_git.is_dir() {
+            dot_git
+        } else {
+            // Worktrees and submodules use a `gitdir: <path>` file.
+            let link = fs::read_to_string(&dot_git).ok()?;
+            let target = PathBuf::from(link.strip_prefix("gitdir:")?.trim());
+            dir.join(target)
+        };
+        Some(Repo {
+            work_tree: dir.to_path_buf(),
+            git_dir,
+        })
+    })
+}
+
+impl Repo {
+    /// The checked-out branch, or `None` on a detached HEAD.
+    pub fn branch(&self) -> Option<String> {
+        let head = fs::read_to_string(self.git_dir.join("HEAD")).ok()?;
+        head.trim()
+            .strip_prefix("ref: refs/heads/")
+            .map(str::to_string)
+    }
+
+    /// Branch name, or the abbreviated commit when HEAD is detached.
+    pub fn describe_head(&self) -> Option<String> {
+        self.branch().or_else(|| {
+            let head = fs::read_to_string(self.git_dir.join("HEAD")).ok()?;
+            Some(head.trim().chars().take(7).collect())
+        })
+    }
+}
+
+/// Opens the repository containing `path`. Both `path` and the repository's
+/// working tree must lie inside a granted workspace, since the callers
+/// write to the repository.
+pub fn open_in_scope(scope: &WorkspaceScope, path: &str) -> CommandResult<(Repo, Repository)> {
+    let resolved = scope.resolve(path)?;
+    let repo = find_repo(&resolved)
+        .ok_or_else(|| CommandError::NotFound(format!("{} is not in a git repository", path)))?;
+    scope.resolve(&repo.work_tree.display().to_string())?;
+    let repository = Repository::open(&repo.work_tree)?;
+    Ok((repo, repository))
+}
+
+/// Refuses to commit or switch branches on a detached HEAD or while a merge,
+/// rebase or similar is in progress. Returns the current branch name.
+pub fn ensure_committable(repo: &Repository) -> CommandResult<String> {
+    if repo.state() != RepositoryState::Clean {
+        return Err(CommandError::InvalidInput(format!(
+            "A {:?} is in progress; finish or abort it first",
+            repo.state()
+        )));
+    }
+    if repo.index()?.has_conflicts() {
+        return Err(CommandError::InvalidInput(
+            "The index has unresolved conflicts".to_string(),
+        ));
+    }
+    if repo.head_detached()? {
+        return Err(CommandError::InvalidInput(
+            "HEAD is detached; check out a branch first".to_string(),
+        ));
+    }
+    let head = repo
+        .find_reference("HEAD")?
+        .symbolic_target()
+        .and_then(|t| t.strip_prefix("refs/heads/"))
+        .map(str::to_string);
+    head.ok_or_else(|| CommandError::InvalidInput("HEAD is not on a branch".to_string()))
+}
+
+/// Turns a path given relative to the working tree, or absolute inside it,
+/// into the relative form git uses. Anything escaping the tree is refused.
+pub fn relative_path(work_tree: &Path, file: &str) -> CommandResult<PathBuf> {
+    let path = Path::new(file);
+    let relative = if path.is_absolute() {
+        path.strip_prefix(work_tree)
+            .map_err(|_| CommandError::OutOfScope(file.to_string()))?
+    } else {
+        path
+    };
+    if relative.as_os_str().is_empty()
+        || relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(CommandError::InvalidInput(file.to_string()));
+    }
+    let normal: PathBuf = relative
+        .components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .collect();
+    if normal.as_os_str().is_empty() {
+        return Err(CommandError::InvalidInput(file.to_string()));
+    }
+    Ok(normal)
+}
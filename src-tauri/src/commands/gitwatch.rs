@@ -0,0 +1,340 @@
+use super::context::{record_event, Agent, ContextEvent, ContextSession, EventType};
+use super::error::CommandResult;
+use super::git::{find_repo, Repo};
+use super::scope::WorkspaceScope;
+use git2::{Oid, Repository, Sort};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+const DEBOUNCE: Duration = Duration::from_millis(300);
+/// Files directly in the git dir whose changes can mean a commit, checkout
+/// or merge. Everything under `refs/heads` in the common dir is watched as
+/// well, along with `packed-refs`; for a linked worktree the two differ.
+const WATCHED_FILES: &[&str] = &["HEAD", "index", "MERGE_HEAD"];
+/// Reflog messages git and libgit2 write when a commit is made in this
+/// working tree, as opposed to one fetched, pulled or checked out.
+const LOCAL_COMMIT: &[&str] = &[
+    "commit:",
+    "commit (initial):",
+    "commit (amend):",
+    "commit (merge):",
+];
+/// Caps the commits reported for one HEAD move, e.g. after a large pull.
+const MAX_COMMITS: usize = 20;
+
+/// What the events are derived from: the state of a repository as of the
+/// last time anything in its git dir settled.
+#[derive(Default, PartialEq)]
+struct Snapshot {
+    branch: Option<String>,
+    head: Option<Oid>,
+    merge_head: Option<Oid>,
+    conflicts: BTreeSet<String>,
+}
+
+impl Snapshot {
+    fn read(repo: &Repository) -> Self {
+        let head = repo.head().ok();
+        let merge_head = repo
+            .find_reference("MERGE_HEAD")
+            .ok()
+            .and_then(|r| r.target());
+        let conflicts = repo
+            .index()
+            .ok()
+            .and_then(|index| index.conflicts().ok())
+            .map(|conflicts| {
+                conflicts
+                    .flatten()
+                    .filter_map(|c| c.our.or(c.their).or(c.ancestor))
+                    .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+                    .collect()
+            })
+            .unwrap_or_default();
+        // Read from the symbolic ref rather than `head` so an unborn branch
+        // still has a name and its first commit is not taken for a checkout.
+        let branch = repo
+            .find_reference("HEAD")
+            .ok()
+            .and_then(|r| r.symbolic_target().map(str::to_string))
+            .and_then(|target| target.strip_prefix("refs/heads/").map(str::to_string));
+        Snapshot {
+            branch,
+            head: head.and_then(|r| r.target()),
+            merge_head,
+            conflicts,
+        }
+    }
+}
+
+struct Tracked {
+    repo: Repo,
+    common_dir: PathBuf,
+    snapshot: Snapshot,
+    dirty_since: Option<Instant>,
+}
+
+/// Watches the git dir of every repository containing a granted workspace
+/// and turns HEAD, ref, index and MERGE_HEAD changes into `Agent::Git`
+/// events. Repository state is read through libgit2, never by running git.
+#[derive(Default)]
+pub struct GitWatcher {
+    inner: Mutex<Option<(RecommendedWatcher, JoinHandle<()>)>>,
+}
+
+impl GitWatcher {
+    pub fn start(&self, app: AppHandle, roots: Vec<PathBuf>) -> notify::Result<Vec<String>> {
+        let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+        let mut watcher = notify::recommended_watcher(move |res| {
+            let _ = tx.send(res);
+        })?;
+
+        let mut repos: HashMap<PathBuf, Tracked> = HashMap::new();
+        for repo in roots.iter().filter_map(|root| find_repo(root)) {
+            let Ok(git_dir) = repo.git_dir.canonicalize() else {
+                continue;
+            };
+            if repos.contains_key(&git_dir) {
+                continue;
+            }
+            let Ok(repository) = Repository::open(&git_dir) else {
+                continue;
+            };
+            let common_dir = repository
+                .commondir()
+                .canonicalize()
+                .unwrap_or_else(|_| git_dir.clone());
+            watcher.watch(&git_dir, RecursiveMode::NonRecursive)?;
+            if common_dir != git_dir {
+                watcher.watch(&common_dir, RecursiveMode::NonRecursive)?;
+            }
+            let heads = common_dir.join("refs").join("heads");
+            if heads.is_dir() {
+                watcher.watch(&heads, RecursiveMode::Recursive)?;
+            }
+            let snapshot = Snapshot::read(&repository);
+            repos.insert(
+                git_dir,
+                Tracked {
+                    repo,
+                    common_dir,
+                    snapshot,
+                    dirty_since: None,
+                },
+            );
+        }
+        let watched = repos
+            .values()
+            .map(|t| t.repo.work_tree.display().to_string())
+            .collect();
+
+        let debouncer = thread::spawn(move || debounce_loop(app, repos, rx));
+        *self.inner.lock().unwrap() = Some((watcher, debouncer));
+        Ok(watched)
+    }
+
+    /// Stops watching and waits for changes already seen to be reported.
+    pub fn stop(&self) {
+        let running = self.inner.lock().unwrap().take();
+        if let Some((watcher, debouncer)) = running {
+            drop(watcher);
+            let _ = debouncer.join();
+        }
+    }
+}
+
+fn is_relevant(git_dir: &Path, common_dir: &Path, path: &Path) -> bool {
+    // Git writes `<file>.lock` and renames it into place; the rename is
+    // reported on the final name.
+    if path.extension().is_some_and(|e| e == "lock") {
+        return false;
+    }
+    let in_git_dir = path
+        .strip_prefix(git_dir)
+        .is_ok_and(|relative| WATCHED_FILES.iter().any(|name| relative == Path::new(name)));
+    let in_common_dir = path.strip_prefix(common_dir).is_ok_and(|relative| {
+        relative.starts_with("refs/heads") || relative == Path::new("packed-refs")
+    });
+    in_git_dir || in_common_dir
+}
+
+fn debounce_loop(
+    app: AppHandle,
+    mut repos: HashMap<PathBuf, Tracked>,
+    rx: mpsc::Receiver<notify::Result<Event>>,
+) {
+    loop {
+        let quiet_for = match rx.recv_timeout(DEBOUNCE) {
+            Ok(Ok(event)) => {
+                for path in &event.paths {
+                    for (git_dir, tracked) in repos.iter_mut() {
+                        if is_relevant(git_dir, &tracked.common_dir, path) {
+                            tracked.dirty_since = Some(Instant::now());
+                        }
+                    }
+                }
+                DEBOUNCE
+            }
+            Ok(Err(_)) | Err(RecvTimeoutError::Timeout) => DEBOUNCE,
+            Err(RecvTimeoutError::Disconnected) => Duration::ZERO,
+        };
+        for (git_dir, tracked) in repos.iter_mut() {
+            if tracked
+                .dirty_since
+                .is_some_and(|since| since.elapsed() >= quiet_for)
+            {
+                tracked.dirty_since = None;
+                if let Ok(repo) = Repository::open(git_dir) {
+                    let next = Snapshot::read(&repo);
+                    report(&app, &repo, &tracked.repo, &tracked.snapshot, &next);
+                    tracked.snapshot = next;
+                }
+            }
+        }
+        if quiet_for.is_zero() {
+            return;
+        }
+    }
+}
+
+/// Emits one event per observable change between two snapshots.
+fn report(app: &AppHandle, repo: &Repository, tracked: &Repo, before: &Snapshot, after: &Snapshot) {
+    if before == after {
+        return;
+    }
+    let work_tree = tracked.work_tree.display().to_string();
+
+    if before.branch != after.branch && after.branch.is_some() {
+        emit(
+            app,
+            EventType::BranchSwitched,
+            serde_json::json!({
+                "repo": work_tree,
+                "from": before.branch,
+                "branch": after.branch,
+                "commit": after.head.map(|oid| oid.to_string()),
+            }),
+        );
+    } else if before.head != after.head {
+        if let Some(head) = after.head {
+            let local = local_commits(repo, before.head);
+            for oid in new_commits(repo, before.head, head) {
+                if !local.contains(&oid) {
+                    continue;
+                }
+                if let Some(payload) = commit_payload(repo, oid, &work_tree, &after.branch) {
+                    emit(app, EventType::CommitCreated, payload);
+                }
+            }
+        }
+    }
+
+    if !after.conflicts.is_empty() && after.conflicts != before.conflicts {
+        emit(
+            app,
+            EventType::MergeConflict,
+            serde_json::json!({
+                "repo": work_tree,
+                "branch": after.branch,
+                "merge_head": after.merge_head.map(|oid| oid.to_string()),
+                "files": after.conflicts,
+            }),
+        );
+    }
+}
+
+/// Commits reachable from `head` but not from `previous`, oldest first.
+/// Moving HEAD backwards (a reset) yields nothing.
+fn new_commits(repo: &Repository, previous: Option<Oid>, head: Oid) -> Vec<Oid> {
+    let Ok(mut walk) = repo.revwalk() else {
+        return Vec::new();
+    };
+    let _ = walk.set_sorting(Sort::TOPOLOGICAL);
+    if walk.push(head).is_err() {
+        return Vec::new();
+    }
+    if let Some(previous) = previous {
+        let _ = walk.hide(previous);
+    }
+    let mut commits: Vec<Oid> = walk.flatten().take(MAX_COMMITS).collect();
+    commits.reverse();
+    commits
+}
+
+/// Commits HEAD's reflog says were made here since it pointed at
+/// `previous`. Fast-forwards from a pull or merge of fetched work have
+/// other messages and are left out.
+fn local_commits(repo: &Repository, previous: Option<Oid>) -> HashSet<Oid> {
+    let Ok(reflog) = repo.reflog("HEAD") else {
+        return HashSet::new();
+    };
+    reflog
+        .iter()
+        .take_while(|entry| Some(entry.id_new()) != previous)
+        .take(MAX_COMMITS)
+        .filter(|entry| {
+            entry
+                .message()
+                .is_some_and(|m| LOCAL_COMMIT.iter().any(|prefix| m.starts_with(prefix)))
+        })
+        .map(|entry| entry.id_new())
+        .collect()
+}
+
+fn commit_payload(
+    repo: &Repository,
+    oid: Oid,
+    work_tree: &str,
+    branch: &Option<String>,
+) -> Option<serde_json::Value> {
+    let commit = repo.find_commit(oid).ok()?;
+    let parent_tree = commit.parent(0).ok().and_then(|p| p.tree().ok());
+    let stats = repo
+        .diff_tree_to_tree(parent_tree.as_ref(), commit.tree().ok().as_ref(), None)
+        .and_then(|diff| diff.stats())
+        .ok();
+    let author = commit.author();
+    Some(serde_json::json!({
+        "repo": work_tree,
+        "branch": branch,
+        "hash": oid.to_string(),
+        "message": commit.message().unwrap_or_default().trim(),
+        "author": author.name(),
+        "email": author.email(),
+        "is_merge": commit.parent_count() > 1,
+        "files_changed": stats.as_ref().map(|s| s.files_changed()),
+        "insertions": stats.as_ref().map(|s| s.insertions()),
+        "deletions": stats.as_ref().map(|s| s.deletions()),
+    }))
+}
+
+fn emit(app: &AppHandle, event_type: EventType, payload: serde_json::Value) {
+    let session_id = app.state::<ContextSession>().id();
+    record_event(
+        app,
+        ContextEvent::new(&session_id, Agent::Git, event_type, payload),
+    );
+}
+
+/// (Re)starts git watching over the repositories of the current workspace
+/// grants and returns their working trees.
+#[tauri::command]
+pub fn start_git_watcher(
+    app: AppHandle,
+    scope: State<'_, WorkspaceScope>,
+    watcher: State<'_, GitWatcher>,
+) -> CommandResult<Vec<String>> {
+    watcher.stop();
+    Ok(watcher.start(app, scope.roots())?)
+}
+
+#[tauri::command]
+pub fn stop_git_watcher(watcher: State<'_, GitWatcher>) {
+    watcher.stop();
+}
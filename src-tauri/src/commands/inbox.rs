@@ -0,0 +1,202 @@
+use super::error::CommandResult;
+use super::notifications::NotificationRequest;
+use super::policy::NotificationOutcome;
+use super::store::JsonStore;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+const INBOX_FILE: &str = "notifications.json";
+/// Oldest entries are dropped past this many.
+const MAX_RECORDS: usize = 1000;
+
+/// One notification the app tried to show, whatever the policy decided.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationRecord {
+    pub id: String,
+    /// Id assigned by the notification server; absent when not delivered.
+    pub notification_id: Option<u32>,
+    pub title: String,
+    pub body: String,
+    pub category: Option<String>,
+    pub outcome: NotificationOutcome,
+    pub created_at: DateTime<Utc>,
+    pub read: bool,
+    pub action: Option<String>,
+    pub action_at: Option<DateTime<Utc>>,
+}
+
+pub struct Inbox {
+    records: Mutex<Vec<NotificationRecord>>,
+    store: JsonStore,
+}
+
+impl Inbox {
+    pub fn load(data_dir: Option<PathBuf>) -> Self {
+        let store = JsonStore::new(data_dir.as_deref(), INBOX_FILE);
+        let records = store.load().unwrap_or_default();
+        Inbox {
+            records: Mutex::new(records),
+            store,
+        }
+    }
+
+    fn persist(&self, records: &[NotificationRecord]) {
+        let _ = self.store.save(records);
+    }
+
+    pub fn record(
+        &self,
+        request: &NotificationRequest,
+        outcome: &NotificationOutcome,
+    ) -> NotificationRecord {
+        let record = NotificationRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            notification_id: delivered_id(outcome),
+            title: request.title.clone(),
+            body: request.body.clone(),
+            category: request.category.clone(),
+            outcome: outcome.clone(),
+            created_at: Utc::now(),
+            read: false,
+            action: None,
+            action_at: None,
+        };
+        let mut records = self.records.lock().unwrap();
+        records.push(record.clone());
+        if records.len() > MAX_RECORDS {
+            let excess = records.len() - MAX_RECORDS;
+            records.drain(..excess);
+        }
+        self.persist(&records);
+        record
+    }
+
+    /// Replaces the outcome of the record with `id` once delivery has been
+    /// attempted. Returns the updated record unless it was cleared meanwhile.
+    pub fn settle(&self, id: &str, outcome: &NotificationOutcome) -> Option<NotificationRecord> {
+        let mut records = self.records.lock().unwrap();
+        let record = records.iter_mut().rev().find(|r| r.id == id)?;
+        record.notification_id = delivered_id(outcome);
+        record.outcome = outcome.clone();
+        let record = record.clone();
+        self.persist(&records);
+        Some(record)
+    }
+
+    /// Stores the action the user clicked on the most recent record shown
+    /// under `notification_id`, and marks it read.
+    pub fn record_action(&self, notification_id: u32, action: &str) {
+        let mut records = self.records.lock().unwrap();
+        if let Some(record) = records
+            .iter_mut()
+            .rev()
+            .find(|r| r.notification_id == Some(notification_id))
+        {
+            record.action = Some(action.to_string());
+            record.action_at = Some(Utc::now());
+            record.read = true;
+            self.persist(&records);
+        }
+    }
+
+    pub fn list(
+        &self,
+        unread_only: bool,
+        category: Option<&str>,
+        limit: usize,
+    ) -> Vec<NotificationRecord> {
+        self.records
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .filter(|r| !unread_only || !r.read)
+            .filter(|r| category.is_none() || r.category.as_deref() == category)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Marks the given records read, or every record when `ids` is `None`.
+    pub fn mark_read(&self, ids: Option<&[String]>) -> usize {
+        let mut records = self.records.lock().unwrap();
+        let mut changed = 0;
+        for record in records.iter_mut() {
+            let selected = match ids {
+                Some(ids) => ids.contains(&record.id),
+                None => true,
+            };
+            if !record.read && selected {
+                record.read = true;
+                changed += 1;
+            }
+        }
+        if changed > 0 {
+            self.persist(&records);
+        }
+        changed
+    }
+
+    pub fn clear(&self) {
+        let mut records = self.records.lock().unwrap();
+        records.clear();
+        self.persist(&records);
+    }
+}
+
+fn delivered_id(outcome: &NotificationOutcome) -> Option<u32> {
+    match outcome {
+        NotificationOutcome::Delivered { id } if *id != 0 => Some(*id),
+        _ => None,
+    }
+}
+
+/// Records `request` as pending, runs `deliver`, then stores its outcome,
+/// or the error as `Failed`, and tells the dashboard about it. The record
+/// is on disk before the notification server is contacted.
+pub fn record_delivery(
+    app: &AppHandle,
+    request: &NotificationRequest,
+    deliver: impl FnOnce() -> CommandResult<NotificationOutcome>,
+) -> CommandResult<NotificationOutcome> {
+    let inbox = app.state::<Inbox>();
+    let pending = inbox.record(request, &NotificationOutcome::Pending);
+    let result = deliver();
+    let outcome = match &result {
+        Ok(outcome) => outcome.clone(),
+        Err(e) => NotificationOutcome::Failed {
+            error: e.to_string(),
+        },
+    };
+    if let Some(record) = inbox.settle(&pending.id, &outcome) {
+        let _ = app.emit_all("notification-recorded", &record);
+    }
+    result
+}
+
+#[tauri::command]
+pub fn list_notifications(
+    unread_only: Option<bool>,
+    category: Option<String>,
+    limit: Option<usize>,
+    inbox: State<'_, Inbox>,
+) -> Vec<NotificationRecord> {
+    inbox.list(
+        unread_only.unwrap_or(false),
+        category.as_deref(),
+        limit.unwrap_or(MAX_RECORDS),
+    )
+}
+
+#[tauri::command]
+pub fn mark_read(ids: Option<Vec<String>>, inbox: State<'_, Inbox>) -> usize {
+    inbox.mark_read(ids.as_deref())
+}
+
+#[tauri::command]
+pub fn clear_notifications(inbox: State<'_, Inbox>) {
+    inbox.clear();
+}
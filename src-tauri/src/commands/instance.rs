@@ -0,0 +1,219 @@
+use serde::{Deserialize, Serialize};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use std::path::Path;
+use std::path::PathBuf;
+#[cfg(unix)]
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// What a second launch hands to the running instance, emitted to the
+/// frontend as `second-instance`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceArgs {
+    pub args: Vec<String>,
+    pub cwd: Option<String>,
+}
+
+impl InstanceArgs {
+    pub fn current() -> Self {
+        InstanceArgs {
+            args: std::env::args().skip(1).collect(),
+            cwd: std::env::current_dir()
+                .ok()
+                .map(|d| d.display().to_string()),
+        }
+    }
+}
+
+/// Proof that this process is the only running instance. Releasing it
+/// removes the socket so the next launch does not have to clean up.
+pub struct InstanceLock {
+    path: Option<PathBuf>,
+    #[cfg(unix)]
+    listener: std::sync::Mutex<Option<UnixListener>>,
+}
+
+impl InstanceLock {
+    pub fn release(&self) {
+        if let Some(path) = &self.path {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// A client that sends no complete line within this is dropped, so one stuck
+/// client cannot block later launches.
+#[cfg(unix)]
+const READ_TIMEOUT: Duration = Duration::from_secs(2);
+/// Upper bound on a forwarded argument line.
+#[cfg(unix)]
+const MAX_LINE: u64 = 64 * 1024;
+
+#[cfg(unix)]
+fn current_uid() -> libc::uid_t {
+    // SAFETY: getuid has no preconditions and cannot fail.
+    unsafe { libc::getuid() }
+}
+
+/// Whether `dir` is a real directory owned by this user that no one else can
+/// enter, so no other user can plant or replace the socket inside it.
+#[cfg(unix)]
+fn is_private_dir(dir: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    std::fs::symlink_metadata(dir)
+        .is_ok_and(|m| m.is_dir() && m.uid() == current_uid() && m.mode() & 0o077 == 0)
+}
+
+/// `$XDG_RUNTIME_DIR`, or else a 0700 directory of our own under the temp
+/// directory. `None` if neither is private to this user.
+#[cfg(unix)]
+fn socket_dir() -> Option<PathBuf> {
+    use std::os::unix::fs::DirBuilderExt;
+
+    if let Some(dir) = std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from) {
+        if is_private_dir(&dir) {
+            return Some(dir);
+        }
+    }
+    let dir = std::env::temp_dir().join(format!("pulsedev-ccm-{}", current_uid()));
+    let _ = std::fs::DirBuilder::new().mode(0o700).create(&dir);
+    is_private_dir(&dir).then_some(dir)
+}
+
+/// User id of the process at the other end of `stream`.
+#[cfg(target_os = "linux")]
+fn peer_uid(stream: &UnixStream) -> Option<libc::uid_t> {
+    use std::os::unix::io::AsRawFd;
+
+    let mut cred = libc::ucred {
+        pid: 0,
+        uid: 0,
+        gid: 0,
+    };
+    let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    // SAFETY: `cred` and `len` describe a writable buffer of the size
+    // SO_PEERCRED expects, and the descriptor is owned by `stream`.
+    let rc = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut cred as *mut libc::ucred as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    (rc == 0).then_some(cred.uid)
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn peer_uid(stream: &UnixStream) -> Option<libc::uid_t> {
+    use std::os::unix::io::AsRawFd;
+
+    let (mut uid, mut gid) = (0, 0);
+    // SAFETY: both out-pointers are valid and the descriptor is owned by
+    // `stream`.
+    let rc = unsafe { libc::getpeereid(stream.as_raw_fd(), &mut uid, &mut gid) };
+    (rc == 0).then_some(uid)
+}
+
+#[cfg(unix)]
+fn same_user(stream: &UnixStream) -> bool {
+    peer_uid(stream) == Some(current_uid())
+}
+
+/// Becomes the running instance, or hands `args` to the one already running
+/// and returns `None`, in which case the caller should exit. Arguments are
+/// only handed to a process of the same user.
+#[cfg(unix)]
+pub fn claim_instance(args: &InstanceArgs) -> Option<InstanceLock> {
+    use std::io::{ErrorKind, Write};
+
+    let unlocked = InstanceLock {
+        path: None,
+        listener: std::sync::Mutex::new(None),
+    };
+    // Without a private directory the socket could be hijacked; run
+    // unlocked rather than not at all.
+    let Some(path) = socket_dir().map(|dir| dir.join("pulsedev-ccm.sock")) else {
+        return Some(unlocked);
+    };
+    // Two attempts: the first may only find a socket left by a crash.
+    for _ in 0..2 {
+        if let Ok(mut stream) = UnixStream::connect(&path) {
+            if !same_user(&stream) {
+                return Some(unlocked);
+            }
+            let mut line = serde_json::to_string(args).unwrap_or_default();
+            line.push('\n');
+            let _ = stream.write_all(line.as_bytes());
+            return None;
+        }
+        match UnixListener::bind(&path) {
+            Ok(listener) => {
+                return Some(InstanceLock {
+                    path: Some(path),
+                    listener: std::sync::Mutex::new(Some(listener)),
+                })
+            }
+            // Another launch won the race; forward to it on the next pass.
+            Err(e) if e.kind() == ErrorKind::AddrInUse => {
+                if UnixStream::connect(&path).is_err() {
+                    let _ = std::fs::remove_file(&path);
+                }
+            }
+            Err(_) => break,
+        }
+    }
+    Some(unlocked)
+}
+
+#[cfg(not(unix))]
+pub fn claim_instance(_args: &InstanceArgs) -> Option<InstanceLock> {
+    Some(InstanceLock { path: None })
+}
+
+/// Brings the main window to the front.
+pub fn focus_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// Accepts arguments forwarded by later launches of the same user.
+#[cfg(unix)]
+pub fn spawn_instance_listener(app: AppHandle) {
+    use super::deeplink::handle_deep_link_args;
+    use std::io::{BufRead, BufReader, Read};
+
+    let Some(listener) = app.state::<InstanceLock>().listener.lock().unwrap().take() else {
+        return;
+    };
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if !same_user(&stream) || stream.set_read_timeout(Some(READ_TIMEOUT)).is_err() {
+                continue;
+            }
+            let mut line = String::new();
+            if BufReader::new(stream.take(MAX_LINE))
+                .read_line(&mut line)
+                .is_err()
+            {
+                continue;
+            }
+            let Ok(args) = serde_json::from_str::<InstanceArgs>(&line) else {
+                continue;
+            };
+            focus_main_window(&app);
+            let _ = app.emit_all("second-instance", &args);
+            handle_deep_link_args(&app, &args.args);
+        }
+    });
+}
+
+#[cfg(not(unix))]
+pub fn spawn_instance_listener(_app: AppHandle) {}
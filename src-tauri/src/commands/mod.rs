@@ -1,5 +1,27 @@
+pub mod api;
+pub mod autocommit;
+pub mod branchname;
+pub mod capture;
+pub mod commitmsg;
+pub mod context;
+pub mod deeplink;
 pub mod dnd;
+pub mod error;
 pub mod filesystem;
+pub mod focus;
+pub mod git;
+pub mod gitwatch;
+pub mod inbox;
+pub mod instance;
 pub mod notifications;
+pub mod policy;
+pub mod queue;
+pub mod scope;
+pub mod shortcuts;
+pub mod shutdown;
+pub mod store;
+pub mod tracking;
 pub mod tray;
+pub mod watcher;
+pub mod widget;
 pub mod window;
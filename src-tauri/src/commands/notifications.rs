@@ -1,10 +1,213 @@
+use super::error::{CommandError, CommandResult};
+use super::inbox::Inbox;
+use super::policy::{self, NotificationOutcome, Priority};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::thread;
 use tauri::api::notification::Notification;
+use tauri::{AppHandle, Manager, State};
+use zbus::zvariant::Value;
+
+const APP_ID: &str = "dev.pulse.ccm";
+const DEST: &str = "org.freedesktop.Notifications";
+const PATH: &str = "/org/freedesktop/Notifications";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationAction {
+    pub id: String,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Urgency {
+    Low,
+    #[default]
+    Normal,
+    Critical,
+}
+
+impl Urgency {
+    fn as_byte(self) -> u8 {
+        match self {
+            Urgency::Low => 0,
+            Urgency::Normal => 1,
+            Urgency::Critical => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotificationRequest {
+    pub title: String,
+    pub body: String,
+    #[serde(default)]
+    pub actions: Vec<NotificationAction>,
+    #[serde(default)]
+    pub urgency: Urgency,
+    /// Policy priority; derived from `urgency` when not given.
+    pub priority: Option<Priority>,
+    /// Freedesktop category hint, e.g. `pulsedev.stuck` or `pulsedev.break`.
+    pub category: Option<String>,
+    pub icon: Option<String>,
+    /// `None` leaves expiry to the notification server; `Some(0)` never expires.
+    pub timeout_ms: Option<i32>,
+    /// Id returned by an earlier notification that this one replaces.
+    pub replaces_id: Option<u32>,
+}
+
+/// Emitted as `notification-action` when the user clicks an action button.
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationActionEvent {
+    pub id: u32,
+    pub action: String,
+    pub category: Option<String>,
+}
+
+/// Sends notifications through the freedesktop Notifications D-Bus interface
+/// when a session bus is available, and through Tauri's notification API
+/// (title and body only) otherwise.
+pub struct Notifier {
+    connection: Option<zbus::blocking::Connection>,
+    categories: Mutex<HashMap<u32, Option<String>>>,
+}
+
+impl Notifier {
+    pub fn connect() -> Self {
+        Notifier {
+            connection: zbus::blocking::Connection::session().ok(),
+            categories: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Shows `request` and returns the server-assigned id (0 on the fallback
+    /// path, where ids are not available).
+    pub fn notify(&self, request: &NotificationRequest) -> CommandResult<u32> {
+        let Some(connection) = &self.connection else {
+            Notification::new(APP_ID)
+                .title(&request.title)
+                .body(&request.body)
+                .show()
+                .map_err(|e| CommandError::BackendUnavailable(format!("Notification: {}", e)))?;
+            return Ok(0);
+        };
+
+        let proxy = zbus::blocking::Proxy::new(connection, DEST, PATH, DEST)?;
+        let actions: Vec<&str> = request
+            .actions
+            .iter()
+            .flat_map(|a| [a.id.as_str(), a.label.as_str()])
+            .collect();
+        let mut hints: HashMap<&str, Value> = HashMap::new();
+        hints.insert("urgency", Value::U8(request.urgency.as_byte()));
+        hints.insert("desktop-entry", Value::from(APP_ID));
+        if let Some(category) = &request.category {
+            hints.insert("category", Value::from(category.as_str()));
+        }
+        let id: u32 = proxy.call(
+            "Notify",
+            &(
+                "PulseDev+",
+                request.replaces_id.unwrap_or(0),
+                request.icon.as_deref().unwrap_or(APP_ID),
+                request.title.as_str(),
+                request.body.as_str(),
+                actions,
+                hints,
+                request.timeout_ms.unwrap_or(-1),
+            ),
+        )?;
+        self.categories
+            .lock()
+            .unwrap()
+            .insert(id, request.category.clone());
+        Ok(id)
+    }
+
+    pub fn close(&self, id: u32) -> CommandResult<()> {
+        let Some(connection) = &self.connection else {
+            return Err(CommandError::Unsupported(
+                "Closing notifications needs a notification server".to_string(),
+            ));
+        };
+        let proxy = zbus::blocking::Proxy::new(connection, DEST, PATH, DEST)?;
+        proxy.call::<_, _, ()>("CloseNotification", &(id,))?;
+        Ok(())
+    }
+}
+
+/// Forwards `ActionInvoked` signals for our notifications to the frontend and
+/// forgets notifications once the server reports them closed. Both signals
+/// are read from one stream: servers send `NotificationClosed` right after
+/// `ActionInvoked`, so handling them in order keeps the click from being
+/// looked up after its notification was forgotten.
+pub fn spawn_action_listener(app: AppHandle) {
+    let Some(connection) = app.state::<Notifier>().connection.clone() else {
+        return;
+    };
+    thread::spawn(move || {
+        let Ok(proxy) = zbus::blocking::Proxy::new(&connection, DEST, PATH, DEST) else {
+            return;
+        };
+        let Ok(signals) = proxy.receive_all_signals() else {
+            return;
+        };
+        for message in signals {
+            let notifier = app.state::<Notifier>();
+            match message.member().as_ref().map(|m| m.as_str()) {
+                Some("ActionInvoked") => {
+                    let Ok((id, action)) = message.body::<(u32, String)>() else {
+                        continue;
+                    };
+                    let Some(category) = notifier.categories.lock().unwrap().get(&id).cloned()
+                    else {
+                        // Another application's notification.
+                        continue;
+                    };
+                    app.state::<Inbox>().record_action(id, &action);
+                    let _ = app.emit_all(
+                        "notification-action",
+                        NotificationActionEvent {
+                            id,
+                            action,
+                            category,
+                        },
+                    );
+                }
+                Some("NotificationClosed") => {
+                    if let Ok((id, _reason)) = message.body::<(u32, u32)>() {
+                        notifier.categories.lock().unwrap().remove(&id);
+                    }
+                }
+                _ => {}
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn send_notification(title: String, body: String, app: AppHandle) -> CommandResult<()> {
+    policy::dispatch(
+        &app,
+        NotificationRequest {
+            title,
+            body,
+            ..Default::default()
+        },
+    )
+    .map(|_| ())
+}
+
+#[tauri::command]
+pub fn send_rich_notification(
+    notification: NotificationRequest,
+    app: AppHandle,
+) -> CommandResult<NotificationOutcome> {
+    policy::dispatch(&app, notification)
+}
 
 #[tauri::command]
-pub fn send_notification(title: String, body: String) -> Result<(), String> {
-    Notification::new("dev.pulse.ccm")
-        .title(&title)
-        .body(&body)
-        .show()
-        .map_err(|e| format!("Notification error: {}", e))
+pub fn close_notification(id: u32, notifier: State<'_, Notifier>) -> CommandResult<()> {
+    notifier.close(id)
 }
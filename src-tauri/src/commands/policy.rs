@@ -0,0 +1,385 @@
+use super::dnd::Dnd;
+use super::error::CommandResult;
+use super::focus::Focus;
+use super::inbox::record_delivery;
+use super::notifications::{NotificationRequest, Notifier, Urgency};
+use super::store::JsonStore;
+use chrono::{Local, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+const POLICY_FILE: &str = "notification_policy.json";
+const RATE_WINDOW: Duration = Duration::from_secs(60 * 60);
+const DIGEST_CHECK: Duration = Duration::from_secs(60);
+const DIGEST_PREVIEW: usize = 5;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    /// Always delivered, even during focus and quiet hours.
+    Critical,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CategoryRule {
+    /// Drop notifications in this category entirely.
+    pub muted: bool,
+    /// Overrides the priority the sender asked for.
+    pub priority: Option<Priority>,
+    /// Maximum deliveries per hour; extra ones go to the digest.
+    pub max_per_hour: Option<u32>,
+    /// Merge notifications arriving within this many seconds into one
+    /// ("5 new achievements") by replacing the previous notification.
+    pub coalesce_secs: Option<u64>,
+    /// Plural noun used in the coalesced title, e.g. `achievements`.
+    pub coalesce_label: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuietHours {
+    /// Local time, `HH:MM`. A window may wrap past midnight.
+    pub start: String,
+    pub end: String,
+}
+
+impl QuietHours {
+    fn contains(&self, now: NaiveTime) -> bool {
+        let (Ok(start), Ok(end)) = (
+            NaiveTime::parse_from_str(&self.start, "%H:%M"),
+            NaiveTime::parse_from_str(&self.end, "%H:%M"),
+        ) else {
+            return false;
+        };
+        if start <= end {
+            now >= start && now < end
+        } else {
+            now >= start || now < end
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PolicyConfig {
+    /// While DND or a focus session is on, only this priority and above
+    /// are shown; the rest wait for the digest.
+    pub focus_min_priority: Priority,
+    pub quiet_hours: Option<QuietHours>,
+    pub categories: HashMap<String, CategoryRule>,
+}
+
+impl Default for PolicyConfig {
+    fn default() -> Self {
+        let mut categories = HashMap::new();
+        categories.insert(
+            "pulsedev.achievement".to_string(),
+            CategoryRule {
+                coalesce_secs: Some(30),
+                coalesce_label: Some("achievements".to_string()),
+                ..Default::default()
+            },
+        );
+        categories.insert(
+            "pulsedev.stuck".to_string(),
+            CategoryRule {
+                max_per_hour: Some(3),
+                ..Default::default()
+            },
+        );
+        PolicyConfig {
+            focus_min_priority: Priority::High,
+            quiet_hours: None,
+            categories,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum NotificationOutcome {
+    Delivered {
+        id: u32,
+    },
+    Suppressed {
+        reason: SuppressReason,
+    },
+    Muted,
+    /// Recorded, and not yet shown or held; what a crash mid-dispatch leaves.
+    Pending,
+    /// The notification server refused it.
+    Failed {
+        error: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuppressReason {
+    Focus,
+    QuietHours,
+    RateLimited,
+}
+
+struct Coalesced {
+    id: u32,
+    count: u32,
+    last: Instant,
+}
+
+/// A notification waiting for the digest.
+struct Held {
+    request: NotificationRequest,
+    reason: SuppressReason,
+    at: Instant,
+}
+
+impl Held {
+    /// Focus and quiet-hours suppressions are due as soon as those end.
+    /// Rate-limited ones wait for their hourly window to roll over, or the
+    /// digest would deliver them within a minute anyway.
+    fn due(&self) -> bool {
+        self.reason != SuppressReason::RateLimited || self.at.elapsed() >= RATE_WINDOW
+    }
+}
+
+#[derive(Default)]
+struct PolicyState {
+    delivered: VecDeque<(Instant, Option<String>)>,
+    coalesced: HashMap<String, Coalesced>,
+    suppressed: Vec<Held>,
+}
+
+pub struct NotificationPolicy {
+    config: Mutex<PolicyConfig>,
+    state: Mutex<PolicyState>,
+    store: JsonStore,
+}
+
+impl NotificationPolicy {
+    pub fn load(data_dir: Option<PathBuf>) -> Self {
+        let store = JsonStore::new(data_dir.as_deref(), POLICY_FILE);
+        let config = store.load().unwrap_or_default();
+        NotificationPolicy {
+            config: Mutex::new(config),
+            state: Mutex::new(PolicyState::default()),
+            store,
+        }
+    }
+
+    pub fn config(&self) -> PolicyConfig {
+        self.config.lock().unwrap().clone()
+    }
+
+    pub fn set_config(&self, config: PolicyConfig) -> CommandResult<()> {
+        self.store.save(&config)?;
+        *self.config.lock().unwrap() = config;
+        Ok(())
+    }
+
+    pub fn pending_digest(&self) -> usize {
+        self.state.lock().unwrap().suppressed.len()
+    }
+
+    fn suppression(&self, priority: Priority, focused: bool) -> Option<SuppressReason> {
+        if priority == Priority::Critical {
+            return None;
+        }
+        let config = self.config.lock().unwrap();
+        if focused && priority < config.focus_min_priority {
+            return Some(SuppressReason::Focus);
+        }
+        if config
+            .quiet_hours
+            .as_ref()
+            .is_some_and(|q| q.contains(Local::now().time()))
+        {
+            return Some(SuppressReason::QuietHours);
+        }
+        None
+    }
+}
+
+fn is_focused(app: &AppHandle) -> bool {
+    app.state::<Focus>().current().is_some() || app.state::<Dnd>().is_enabled().unwrap_or(false)
+}
+
+fn default_priority(request: &NotificationRequest) -> Priority {
+    match request.urgency {
+        Urgency::Low => Priority::Low,
+        Urgency::Normal => Priority::Normal,
+        Urgency::Critical => Priority::Critical,
+    }
+}
+
+/// Runs `request` through the policy and either shows it, holds it for the
+/// digest, or drops it. Every notification the app sends goes through here,
+/// and every outcome lands in the inbox.
+pub fn dispatch(
+    app: &AppHandle,
+    request: NotificationRequest,
+) -> CommandResult<NotificationOutcome> {
+    let recorded = request.clone();
+    record_delivery(app, &recorded, || apply_policy(app, request))
+}
+
+fn apply_policy(
+    app: &AppHandle,
+    mut request: NotificationRequest,
+) -> CommandResult<NotificationOutcome> {
+    let policy = app.state::<NotificationPolicy>();
+    let rule = request
+        .category
+        .as_ref()
+        .and_then(|c| policy.config.lock().unwrap().categories.get(c).cloned())
+        .unwrap_or_default();
+    if rule.muted {
+        return Ok(NotificationOutcome::Muted);
+    }
+    let priority = rule
+        .priority
+        .or(request.priority)
+        .unwrap_or_else(|| default_priority(&request));
+
+    let reason = policy.suppression(priority, is_focused(app)).or_else(|| {
+        let max = rule.max_per_hour?;
+        let mut state = policy.state.lock().unwrap();
+        state.delivered.retain(|(at, _)| at.elapsed() < RATE_WINDOW);
+        let sent = state
+            .delivered
+            .iter()
+            .filter(|(_, c)| c == &request.category)
+            .count();
+        (sent as u32 >= max).then_some(SuppressReason::RateLimited)
+    });
+    if let Some(reason) = reason {
+        policy.state.lock().unwrap().suppressed.push(Held {
+            request,
+            reason,
+            at: Instant::now(),
+        });
+        return Ok(NotificationOutcome::Suppressed { reason });
+    }
+
+    if let (Some(category), Some(window)) = (request.category.clone(), rule.coalesce_secs) {
+        let state = policy.state.lock().unwrap();
+        if let Some(previous) = state.coalesced.get(&category) {
+            if previous.last.elapsed() < Duration::from_secs(window) && previous.id != 0 {
+                let count = previous.count + 1;
+                let label = rule.coalesce_label.as_deref().unwrap_or("notifications");
+                request.replaces_id = Some(previous.id);
+                request.title = format!("{} new {}", count, label);
+            }
+        }
+    }
+
+    let id = app.state::<Notifier>().notify(&request)?;
+    let mut state = policy.state.lock().unwrap();
+    state
+        .delivered
+        .push_back((Instant::now(), request.category.clone()));
+    if let Some(category) = request.category {
+        if rule.coalesce_secs.is_some() {
+            let count = match state.coalesced.get(&category) {
+                Some(previous) if request.replaces_id == Some(previous.id) => previous.count + 1,
+                _ => 1,
+            };
+            state.coalesced.insert(
+                category,
+                Coalesced {
+                    id,
+                    count,
+                    last: Instant::now(),
+                },
+            );
+        }
+    }
+    Ok(NotificationOutcome::Delivered { id })
+}
+
+/// Delivers everything held back that is due as a single summary. Nothing
+/// happens while focus or quiet hours still hold.
+pub fn deliver_digest(app: &AppHandle) -> CommandResult<usize> {
+    let policy = app.state::<NotificationPolicy>();
+    if policy
+        .suppression(Priority::Normal, is_focused(app))
+        .is_some()
+    {
+        return Ok(0);
+    }
+    let held: Vec<Held> = {
+        let mut state = policy.state.lock().unwrap();
+        let (due, waiting): (Vec<Held>, Vec<Held>) = std::mem::take(&mut state.suppressed)
+            .into_iter()
+            .partition(Held::due);
+        state.suppressed = waiting;
+        due
+    };
+    if held.is_empty() {
+        return Ok(0);
+    }
+    let mut lines: Vec<String> = held
+        .iter()
+        .take(DIGEST_PREVIEW)
+        .map(|h| format!("• {}", h.request.title))
+        .collect();
+    if held.len() > DIGEST_PREVIEW {
+        lines.push(format!("…and {} more", held.len() - DIGEST_PREVIEW));
+    }
+    let digest = NotificationRequest {
+        title: format!("{} notifications while you were focused", held.len()),
+        body: lines.join("\n"),
+        category: Some("pulsedev.digest".to_string()),
+        ..Default::default()
+    };
+    let delivered = record_delivery(app, &digest, || {
+        let id = app.state::<Notifier>().notify(&digest)?;
+        Ok(NotificationOutcome::Delivered { id })
+    });
+    if let Err(e) = delivered {
+        // Held again, ahead of anything suppressed since, for the next try.
+        let mut state = policy.state.lock().unwrap();
+        let since = std::mem::replace(&mut state.suppressed, held);
+        state.suppressed.extend(since);
+        return Err(e);
+    }
+    Ok(held.len())
+}
+
+/// Catches the end of quiet hours and DND changes that happen while no
+/// focus session is ending to trigger the digest directly.
+pub fn spawn_digest_timer(app: AppHandle) {
+    thread::spawn(move || loop {
+        thread::sleep(DIGEST_CHECK);
+        if app.state::<NotificationPolicy>().pending_digest() > 0 {
+            let _ = deliver_digest(&app);
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_notification_policy(policy: State<'_, NotificationPolicy>) -> PolicyConfig {
+    policy.config()
+}
+
+#[tauri::command]
+pub fn set_notification_policy(
+    config: PolicyConfig,
+    policy: State<'_, NotificationPolicy>,
+) -> CommandResult<()> {
+    policy.set_config(config)
+}
+
+#[tauri::command]
+pub fn flush_notification_digest(app: AppHandle) -> CommandResult<usize> {
+    deliver_digest(&app)
+}
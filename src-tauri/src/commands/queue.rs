@@ -0,0 +1,335 @@
+use super::api::{ApiError, CcmClient};
+use super::context::ContextEvent;
+use super::store::write_atomic;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::State;
+
+const QUEUE_FILE: &str = "event_queue.jsonl";
+const BATCH_SIZE: usize = 50;
+const BASE_BACKOFF: Duration = Duration::from_secs(2);
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+const IDLE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// One line of the on-disk log.
+#[derive(Clone, Serialize, Deserialize)]
+struct QueuedEvent {
+    id: String,
+    event: ContextEvent,
+}
+
+impl QueuedEvent {
+    /// The event as uploaded, carrying the queue id so that a retry after a
+    /// lost response is not stored twice.
+    fn keyed(&self) -> ContextEvent {
+        ContextEvent {
+            event_id: Some(self.id.clone()),
+            ..self.event.clone()
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct QueueStatus {
+    pub depth: usize,
+    pub consecutive_failures: u32,
+    pub next_retry_in_secs: u64,
+    pub last_error: Option<String>,
+    /// HTTP status of the last failed upload, e.g. 401 for a bad token.
+    pub last_status: Option<u16>,
+    /// Events dropped since launch because the backend rejected them.
+    pub rejected: u64,
+}
+
+struct QueueState {
+    events: VecDeque<QueuedEvent>,
+    failures: u32,
+    next_attempt: Instant,
+    last_error: Option<ApiError>,
+    rejected: u64,
+}
+
+/// How far an upload got through a batch.
+struct Uploaded {
+    /// Events at the front of the batch that are done with, whether stored
+    /// or rejected.
+    done: usize,
+    rejected: usize,
+    error: Option<ApiError>,
+}
+
+/// Durable outbox for context events. Events are appended to a JSON-lines
+/// file before anything else happens to them and are only removed once the
+/// backend has accepted them.
+pub struct EventQueue {
+    state: Mutex<QueueState>,
+    wake: Condvar,
+    upload: Mutex<()>,
+    path: Option<PathBuf>,
+    client: Arc<CcmClient>,
+}
+
+impl EventQueue {
+    pub fn open(data_dir: Option<PathBuf>, client: Arc<CcmClient>) -> Arc<Self> {
+        let path = data_dir.map(|dir| dir.join(QUEUE_FILE));
+        let events = path.as_ref().map(|p| read_log(p)).unwrap_or_default();
+        Arc::new(EventQueue {
+            state: Mutex::new(QueueState {
+                events,
+                failures: 0,
+                next_attempt: Instant::now(),
+                last_error: None,
+                rejected: 0,
+            }),
+            wake: Condvar::new(),
+            upload: Mutex::new(()),
+            path,
+            client,
+        })
+    }
+
+    /// Starts the background uploader. It sleeps until the next retry is due
+    /// or until `enqueue`/`flush_now` wakes it.
+    pub fn spawn_uploader(self: &Arc<Self>) {
+        let queue = Arc::clone(self);
+        thread::spawn(move || loop {
+            {
+                let state = queue.state.lock().unwrap();
+                let wait = if state.events.is_empty() {
+                    IDLE_INTERVAL
+                } else {
+                    state.next_attempt.saturating_duration_since(Instant::now())
+                };
+                if !wait.is_zero() {
+                    let _ = queue.wake.wait_timeout(state, wait).unwrap();
+                }
+            }
+            let due = {
+                let state = queue.state.lock().unwrap();
+                !state.events.is_empty() && Instant::now() >= state.next_attempt
+            };
+            if due {
+                queue.flush();
+            }
+        });
+    }
+
+    pub fn enqueue(&self, event: ContextEvent) {
+        let queued = QueuedEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            event,
+        };
+        // Appending under the state lock keeps it ordered with `rewrite_log`.
+        let mut state = self.state.lock().unwrap();
+        if let Some(path) = &self.path {
+            let _ = append_log(path, &queued);
+        }
+        state.events.push_back(queued);
+        self.wake.notify_one();
+    }
+
+    pub fn status(&self) -> QueueStatus {
+        let state = self.state.lock().unwrap();
+        QueueStatus {
+            depth: state.events.len(),
+            consecutive_failures: state.failures,
+            next_retry_in_secs: state
+                .next_attempt
+                .saturating_duration_since(Instant::now())
+                .as_secs(),
+            last_error: state.last_error.as_ref().map(ApiError::to_string),
+            last_status: match state.last_error {
+                Some(ApiError::Status { code, .. }) => Some(code),
+                _ => None,
+            },
+            rejected: state.rejected,
+        }
+    }
+
+    /// Uploads queued events in batches until the queue is empty or a request
+    /// fails. Returns how many events were stored by the backend.
+    pub fn flush(&self) -> usize {
+        let _uploading = self.upload.lock().unwrap();
+        let mut delivered = 0;
+        loop {
+            let batch: Vec<QueuedEvent> = {
+                let state = self.state.lock().unwrap();
+                state.events.iter().take(BATCH_SIZE).cloned().collect()
+            };
+            if batch.is_empty() {
+                break;
+            }
+            let uploaded = self.upload_batch(&batch);
+            let mut state = self.state.lock().unwrap();
+            state.events.drain(..uploaded.done);
+            state.rejected += uploaded.rejected as u64;
+            delivered += uploaded.done - uploaded.rejected;
+            if uploaded.done > 0 {
+                if let Some(path) = &self.path {
+                    let _ = rewrite_log(path, &state.events);
+                }
+            }
+            if let Some(e) = uploaded.error {
+                state.failures += 1;
+                // A bad token or base URL does not fix itself; wait out the
+                // longest interval unless `flush_now` is asked for.
+                let wait = if e.is_transient() {
+                    backoff(state.failures)
+                } else {
+                    MAX_BACKOFF
+                };
+                state.next_attempt = Instant::now() + wait;
+                state.last_error = Some(e);
+                break;
+            }
+            state.failures = 0;
+            state.last_error = None;
+        }
+        delivered
+    }
+
+    /// Rewrites the on-disk log from memory. Used on shutdown so the log is
+    /// compacted and complete even if an earlier append failed.
+    pub fn persist(&self) -> std::io::Result<()> {
+        let state = self.state.lock().unwrap();
+        match &self.path {
+            Some(path) => rewrite_log(path, &state.events),
+            None => Ok(()),
+        }
+    }
+
+    /// Clears any pending backoff and flushes on the caller's thread.
+    pub fn flush_now(&self) -> usize {
+        self.state.lock().unwrap().next_attempt = Instant::now();
+        self.flush()
+    }
+
+    /// Sends `batch` in one request. Any failure other than a rejection
+    /// (unreachable backend, bad token, wrong base URL) keeps every event
+    /// queued for the next attempt.
+    fn upload_batch(&self, batch: &[QueuedEvent]) -> Uploaded {
+        let events: Vec<ContextEvent> = batch.iter().map(QueuedEvent::keyed).collect();
+        match self.client.post_events(&events.iter().collect::<Vec<_>>()) {
+            Ok(_) => Uploaded {
+                done: batch.len(),
+                rejected: 0,
+                error: None,
+            },
+            // One invalid event fails the whole batch; send them one by one
+            // to find and drop it.
+            Err(e) if e.is_rejection() => self.upload_singly(batch),
+            Err(e) => Uploaded {
+                done: 0,
+                rejected: 0,
+                error: Some(e),
+            },
+        }
+    }
+
+    fn upload_singly(&self, batch: &[QueuedEvent]) -> Uploaded {
+        let mut rejected = 0;
+        for (i, queued) in batch.iter().enumerate() {
+            match self.client.post_event(&queued.keyed()) {
+                Ok(_) => {}
+                // The event itself is invalid; retrying it would wedge the
+                // queue, so it is dropped.
+                Err(e) if e.is_rejection() => rejected += 1,
+                Err(e) => {
+                    return Uploaded {
+                        done: i,
+                        rejected,
+                        error: Some(e),
+                    }
+                }
+            }
+        }
+        Uploaded {
+            done: batch.len(),
+            rejected,
+            error: None,
+        }
+    }
+}
+
+fn backoff(failures: u32) -> Duration {
+    let factor = 2u32.saturating_pow(failures.saturating_sub(1).min(16));
+    BASE_BACKOFF.saturating_mul(factor).min(MAX_BACKOFF)
+}
+
+fn read_log(path: &PathBuf) -> VecDeque<QueuedEvent> {
+    let file = match File::open(path) {
+        Ok(f) => f,
+        Err(_) => return VecDeque::new(),
+    };
+    // A torn final line from a crash mid-append is skipped, not fatal.
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+fn append_log(path: &PathBuf, queued: &QueuedEvent) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    let line = serde_json::to_string(queued).map_err(std::io::Error::other)?;
+    writeln!(file, "{}", line)?;
+    file.sync_data()
+}
+
+fn rewrite_log(path: &PathBuf, events: &VecDeque<QueuedEvent>) -> std::io::Result<()> {
+    let mut contents = Vec::new();
+    for queued in events {
+        serde_json::to_writer(&mut contents, queued).map_err(std::io::Error::other)?;
+        contents.push(b'\n');
+    }
+    write_atomic(path, &contents)
+}
+
+#[tauri::command]
+pub fn event_queue_status(queue: State<'_, Arc<EventQueue>>) -> QueueStatus {
+    queue.status()
+}
+
+#[tauri::command(async)]
+pub fn flush_event_queue(queue: State<'_, Arc<EventQueue>>) -> QueueStatus {
+    queue.flush_now();
+    queue.status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::context::{Agent, EventType};
+    use super::*;
+
+    #[test]
+    fn test_keyed_event_carries_queue_id() {
+        let queued = QueuedEvent {
+            id: "6f1c2a7e-0d4b-4e55-9a35-3f3c1b1e9d10".into(),
+            event: ContextEvent::new(
+                "session",
+                Agent::File,
+                EventType::FileModified,
+                serde_json::json!({ "path": "src/main.rs" }),
+            ),
+        };
+        let body = serde_json::to_value(queued.keyed()).unwrap();
+        assert_eq!(body["eventId"], "6f1c2a7e-0d4b-4e55-9a35-3f3c1b1e9d10");
+        assert_eq!(body["sessionId"], "session");
+
+        // The log keeps the id beside the event, so lines written before
+        // events carried one get the same key.
+        let line = serde_json::to_string(&queued).unwrap();
+        assert!(!line.contains("eventId"));
+        let reloaded: QueuedEvent = serde_json::from_str(&line).unwrap();
+        assert_eq!(reloaded.keyed().event_id, Some(queued.id));
+    }
+}
@@ -0,0 +1,220 @@
+use super::error::{CommandError, CommandResult};
+use super::store::JsonStore;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{api::dialog::blocking::FileDialogBuilder, State, Window};
+
+const SCOPES_FILE: &str = "workspace_scopes.json";
+
+#[derive(Default, Serialize, Deserialize)]
+struct PersistedScopes {
+    roots: Vec<PathBuf>,
+}
+
+/// Directories (or single files) the user has explicitly granted through a
+/// native dialog. Every filesystem command resolves its path against these.
+pub struct WorkspaceScope {
+    roots: Mutex<Vec<PathBuf>>,
+    store: JsonStore,
+}
+
+impl WorkspaceScope {
+    pub fn load(data_dir: Option<PathBuf>) -> Self {
+        let store = JsonStore::new(data_dir.as_deref(), SCOPES_FILE);
+        let roots = store
+            .load::<PersistedScopes>()
+            .map(|p| p.roots)
+            .unwrap_or_default()
+            .into_iter()
+            // Drop grants whose target has disappeared since the last run.
+            .filter_map(|root| root.canonicalize().ok())
+            .collect();
+        WorkspaceScope {
+            roots: Mutex::new(roots),
+            store,
+        }
+    }
+
+    pub fn roots(&self) -> Vec<PathBuf> {
+        self.roots.lock().unwrap().clone()
+    }
+
+    pub fn grant(&self, path: &Path) -> CommandResult<PathBuf> {
+        let canonical = path.canonicalize()?;
+        let mut roots = self.roots.lock().unwrap();
+        if !roots.contains(&canonical) {
+            roots.push(canonical.clone());
+        }
+        self.persist(&roots)?;
+        Ok(canonical)
+    }
+
+    pub fn revoke(&self, path: &Path) -> CommandResult<bool> {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let mut roots = self.roots.lock().unwrap();
+        let before = roots.len();
+        roots.retain(|r| r != &canonical);
+        let removed = roots.len() != before;
+        if removed {
+            self.persist(&roots)?;
+        }
+        Ok(removed)
+    }
+
+    /// Resolves `path` to its canonical form and checks it lies inside a
+    /// granted root. Symlinks are followed before the check, so a link
+    /// pointing outside the workspace is rejected, and a dangling link is
+    /// refused outright since its target cannot be checked. For paths that
+    /// do not exist yet (writes), the parent directory is canonicalized
+    /// instead.
+    pub fn resolve(&self, path: &str) -> CommandResult<PathBuf> {
+        let requested = Path::new(path);
+        if !requested.is_absolute() {
+            return Err(CommandError::InvalidInput(path.to_string()));
+        }
+        let resolved = match fs::symlink_metadata(requested) {
+            Ok(metadata) => match requested.canonicalize() {
+                Ok(p) => p,
+                Err(_) if metadata.file_type().is_symlink() => {
+                    return Err(CommandError::OutOfScope(path.to_string()))
+                }
+                Err(e) => return Err(e.into()),
+            },
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                let file_name = match requested.components().last() {
+                    Some(Component::Normal(name)) => name.to_owned(),
+                    _ => return Err(CommandError::InvalidInput(path.to_string())),
+                };
+                let parent = requested
+                    .parent()
+                    .ok_or_else(|| CommandError::InvalidInput(path.to_string()))?;
+                parent.canonicalize()?.join(file_name)
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let roots = self.roots.lock().unwrap();
+        if roots.iter().any(|root| resolved.starts_with(root)) {
+            Ok(resolved)
+        } else {
+            Err(CommandError::OutOfScope(path.to_string()))
+        }
+    }
+
+    fn persist(&self, roots: &[PathBuf]) -> CommandResult<()> {
+        self.store.save(&PersistedScopes {
+            roots: roots.to_vec(),
+        })?;
+        Ok(())
+    }
+}
+
+/// Async so the blocking dialog waits off the main thread, which has to keep
+/// running the event loop that shows it.
+#[tauri::command(async)]
+pub fn grant_workspace(
+    window: Window,
+    scope: State<'_, WorkspaceScope>,
+) -> CommandResult<Option<String>> {
+    match FileDialogBuilder::new().set_parent(&window).pick_folder() {
+        Some(folder) => scope.grant(&folder).map(|p| Some(p.display().to_string())),
+        None => Ok(None),
+    }
+}
+
+#[tauri::command]
+pub fn list_workspaces(scope: State<'_, WorkspaceScope>) -> Vec<String> {
+    scope
+        .roots()
+        .iter()
+        .map(|p| p.display().to_string())
+        .collect()
+}
+
+#[tauri::command]
+pub fn revoke_workspace(path: String, scope: State<'_, WorkspaceScope>) -> CommandResult<bool> {
+    scope.revoke(Path::new(&path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A scope granting `<tmp>/ws`, and `<tmp>` itself for cleanup.
+    fn workspace() -> (WorkspaceScope, PathBuf) {
+        let tmp = std::env::temp_dir().join(format!("pulsedev-scope-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(tmp.join("ws")).unwrap();
+        let scope = WorkspaceScope::load(None);
+        scope.grant(&tmp.join("ws")).unwrap();
+        (scope, tmp)
+    }
+
+    fn resolve(scope: &WorkspaceScope, path: &Path) -> CommandResult<PathBuf> {
+        scope.resolve(path.to_str().unwrap())
+    }
+
+    #[test]
+    fn test_resolve_existing_file_in_scope() {
+        let (scope, tmp) = workspace();
+        let file = tmp.join("ws/notes.txt");
+        fs::write(&file, "hi").unwrap();
+        assert_eq!(
+            resolve(&scope, &file).unwrap(),
+            file.canonicalize().unwrap()
+        );
+        fs::remove_dir_all(tmp).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_rejects_parent_traversal() {
+        let (scope, tmp) = workspace();
+        fs::write(tmp.join("secret.txt"), "hi").unwrap();
+        for path in ["ws/../secret.txt", "ws/../new.txt"] {
+            assert!(matches!(
+                resolve(&scope, &tmp.join(path)),
+                Err(CommandError::OutOfScope(_))
+            ));
+        }
+        fs::remove_dir_all(tmp).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_new_file_in_scope() {
+        let (scope, tmp) = workspace();
+        let resolved = resolve(&scope, &tmp.join("ws/new.txt")).unwrap();
+        assert_eq!(
+            resolved,
+            tmp.join("ws").canonicalize().unwrap().join("new.txt")
+        );
+        fs::remove_dir_all(tmp).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_rejects_relative_path() {
+        let scope = WorkspaceScope::load(None);
+        assert!(matches!(
+            scope.resolve("ws/new.txt"),
+            Err(CommandError::InvalidInput(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_rejects_symlinks_out_of_scope() {
+        use std::os::unix::fs::symlink;
+
+        let (scope, tmp) = workspace();
+        fs::write(tmp.join("secret.txt"), "hi").unwrap();
+        symlink(tmp.join("secret.txt"), tmp.join("ws/link")).unwrap();
+        symlink(tmp.join("missing.txt"), tmp.join("ws/dangling")).unwrap();
+        for link in ["ws/link", "ws/dangling"] {
+            assert!(matches!(
+                resolve(&scope, &tmp.join(link)),
+                Err(CommandError::OutOfScope(_))
+            ));
+        }
+        fs::remove_dir_all(tmp).unwrap();
+    }
+}
@@ -0,0 +1,236 @@
+use super::api::StuckState;
+use super::capture::open_quick_capture;
+use super::context::{record_event, Agent, ContextEvent, ContextSession, EventType};
+use super::error::{CommandError, CommandResult};
+use super::focus::toggle_focus;
+use super::store::JsonStore;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, GlobalShortcutManager, Manager, State};
+
+const SHORTCUTS_FILE: &str = "shortcuts.json";
+const MODIFIERS: [&str; 4] = ["alt", "ctrl", "shift", "super"];
+/// What `CmdOrCtrl` stands for on this platform.
+#[cfg(target_os = "macos")]
+const CMD_OR_CTRL: &str = "super";
+#[cfg(not(target_os = "macos"))]
+const CMD_OR_CTRL: &str = "ctrl";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShortcutAction {
+    ToggleFocus,
+    MarkStuck,
+    QuickCapture,
+    ToggleWindow,
+}
+
+/// Action → accelerator, e.g. `CmdOrCtrl+Alt+F`. An action with no entry
+/// has no shortcut.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ShortcutConfig {
+    pub bindings: BTreeMap<ShortcutAction, String>,
+}
+
+impl Default for ShortcutConfig {
+    fn default() -> Self {
+        let bindings = [
+            (ShortcutAction::ToggleFocus, "CmdOrCtrl+Alt+F"),
+            (ShortcutAction::MarkStuck, "CmdOrCtrl+Alt+S"),
+            (ShortcutAction::QuickCapture, "CmdOrCtrl+Alt+N"),
+            (ShortcutAction::ToggleWindow, "CmdOrCtrl+Alt+P"),
+        ];
+        ShortcutConfig {
+            bindings: bindings
+                .into_iter()
+                .map(|(action, accelerator)| (action, accelerator.to_string()))
+                .collect(),
+        }
+    }
+}
+
+/// Canonical form of an accelerator, so that `CmdOrCtrl+Alt+F` and
+/// `alt+CommandOrControl+f` compare equal. Modifiers map the way Tauri maps
+/// them: `Cmd` is Super and `Ctrl` is Control, so `Cmd+Alt+F` and
+/// `Ctrl+Alt+F` stay distinct while `CmdOrCtrl` becomes whichever of the
+/// two this platform uses. Global shortcuts without a modifier would
+/// swallow ordinary typing and are rejected.
+fn normalize(accelerator: &str) -> CommandResult<String> {
+    let mut modifiers = Vec::new();
+    let mut key = None;
+    for part in accelerator.split('+').map(|p| p.trim().to_lowercase()) {
+        let part = match part.as_str() {
+            "commandorcontrol" | "commandorctrl" | "cmdorctrl" | "cmdorcontrol" => {
+                CMD_OR_CTRL.to_string()
+            }
+            "ctrl" | "control" => "ctrl".to_string(),
+            "cmd" | "command" | "meta" | "super" => "super".to_string(),
+            "option" => "alt".to_string(),
+            _ => part,
+        };
+        if MODIFIERS.contains(&part.as_str()) {
+            modifiers.push(part);
+        } else if part.is_empty() || key.replace(part).is_some() {
+            return Err(CommandError::InvalidInput(format!(
+                "'{}' must be modifiers plus exactly one key",
+                accelerator
+            )));
+        }
+    }
+    let Some(key) = key else {
+        return Err(CommandError::InvalidInput(format!(
+            "'{}' has no key",
+            accelerator
+        )));
+    };
+    if modifiers.is_empty() {
+        return Err(CommandError::InvalidInput(format!(
+            "'{}' needs at least one modifier",
+            accelerator
+        )));
+    }
+    modifiers.sort();
+    modifiers.dedup();
+    modifiers.push(key);
+    Ok(modifiers.join("+"))
+}
+
+/// Checks every accelerator parses and no two actions share one.
+pub fn validate(config: &ShortcutConfig) -> CommandResult<()> {
+    let mut seen: BTreeMap<String, ShortcutAction> = BTreeMap::new();
+    for (action, accelerator) in &config.bindings {
+        let canonical = normalize(accelerator)?;
+        if let Some(other) = seen.insert(canonical, *action) {
+            return Err(CommandError::InvalidInput(format!(
+                "'{}' is bound to both {:?} and {:?}",
+                accelerator, other, action
+            )));
+        }
+    }
+    Ok(())
+}
+
+pub struct Shortcuts {
+    config: Mutex<ShortcutConfig>,
+    store: JsonStore,
+}
+
+impl Shortcuts {
+    pub fn load(data_dir: Option<PathBuf>) -> Self {
+        let store = JsonStore::new(data_dir.as_deref(), SHORTCUTS_FILE);
+        let config = store
+            .load()
+            .filter(|c| validate(c).is_ok())
+            .unwrap_or_default();
+        Shortcuts {
+            config: Mutex::new(config),
+            store,
+        }
+    }
+
+    pub fn config(&self) -> ShortcutConfig {
+        self.config.lock().unwrap().clone()
+    }
+
+    fn save(&self, config: ShortcutConfig) -> CommandResult<()> {
+        self.store.save(&config)?;
+        *self.config.lock().unwrap() = config;
+        Ok(())
+    }
+}
+
+fn run_action(app: &AppHandle, action: ShortcutAction) {
+    match action {
+        ShortcutAction::ToggleFocus => toggle_focus(app, "Started from shortcut"),
+        ShortcutAction::MarkStuck => {
+            let session_id = app.state::<ContextSession>().id();
+            let stuck = StuckState {
+                session_id: session_id.clone(),
+                detected_at: Utc::now(),
+                pattern: "manual".into(),
+                context: serde_json::json!({ "source": "shortcut" }),
+                resolved: false,
+            };
+            record_event(
+                app,
+                ContextEvent::new(
+                    &session_id,
+                    Agent::Flow,
+                    EventType::StuckState,
+                    serde_json::to_value(stuck).unwrap_or_default(),
+                ),
+            );
+        }
+        ShortcutAction::QuickCapture => {
+            let _ = open_quick_capture(app);
+        }
+        ShortcutAction::ToggleWindow => {
+            let Some(window) = app.get_window("main") else {
+                return;
+            };
+            if window.is_visible().unwrap_or(false) {
+                let _ = window.hide();
+            } else {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        }
+    }
+}
+
+/// Replaces every registered global shortcut with those in `config`.
+/// Registration fails with `Conflict` when another application already holds
+/// a binding.
+fn register(app: &AppHandle, config: &ShortcutConfig) -> CommandResult<()> {
+    let mut manager = app.global_shortcut_manager();
+    manager.unregister_all()?;
+    for (action, accelerator) in &config.bindings {
+        let handle = app.clone();
+        let action = *action;
+        manager
+            .register(accelerator, move || run_action(&handle, action))
+            .map_err(|e| {
+                CommandError::Conflict(format!(
+                    "'{}' is unavailable, another application may hold it: {}",
+                    accelerator, e
+                ))
+            })?;
+    }
+    Ok(())
+}
+
+/// Registers the saved bindings on startup, falling back to the defaults if
+/// they can no longer be registered.
+pub fn register_shortcuts(app: &AppHandle) {
+    let shortcuts = app.state::<Shortcuts>();
+    if register(app, &shortcuts.config()).is_err() {
+        let _ = register(app, &ShortcutConfig::default());
+    }
+}
+
+#[tauri::command]
+pub fn get_shortcuts(shortcuts: State<'_, Shortcuts>) -> ShortcutConfig {
+    shortcuts.config()
+}
+
+/// Validates and applies new bindings immediately. If any of them cannot be
+/// registered the previous bindings are put back and nothing is saved.
+#[tauri::command]
+pub fn set_shortcuts(
+    config: ShortcutConfig,
+    app: AppHandle,
+    shortcuts: State<'_, Shortcuts>,
+) -> CommandResult<()> {
+    validate(&config)?;
+    if let Err(e) = register(&app, &config) {
+        let _ = register(&app, &shortcuts.config());
+        return Err(e);
+    }
+    shortcuts.save(config.clone())?;
+    let _ = app.emit_all("shortcuts-changed", &config);
+    Ok(())
+}
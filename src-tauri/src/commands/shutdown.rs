@@ -0,0 +1,70 @@
+use super::context::{record_event, Agent, ContextEvent, ContextSession, EventType};
+use super::focus::{Focus, FocusEndReason};
+use super::gitwatch::GitWatcher;
+use super::instance::InstanceLock;
+use super::queue::EventQueue;
+use super::watcher::FileWatcher;
+use super::window::WindowStates;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+
+static SHUT_DOWN: AtomicBool = AtomicBool::new(false);
+
+/// Orderly teardown shared by tray quit, SIGTERM and any other real exit.
+/// Runs at most once; later calls return immediately.
+pub fn shutdown(app: &AppHandle, reason: &str) {
+    if SHUT_DOWN.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    // Capture first, so their last events make it into the queue.
+    app.state::<FileWatcher>().stop();
+    app.state::<GitWatcher>().stop();
+
+    app.state::<Focus>().end(app, None, FocusEndReason::AppExit);
+
+    let session_id = app.state::<ContextSession>().id();
+    record_event(
+        app,
+        ContextEvent::new(
+            &session_id,
+            Agent::Flow,
+            EventType::FlowEnd,
+            serde_json::json!({
+                "reason": reason,
+                "end_time": chrono::Utc::now().to_rfc3339(),
+            }),
+        ),
+    );
+
+    let _ = app.state::<Arc<EventQueue>>().persist();
+
+    let windows = app.state::<WindowStates>();
+    for window in app.windows().values() {
+        windows.capture(window);
+    }
+    windows.persist();
+
+    app.state::<InstanceLock>().release();
+}
+
+/// Quits through `shutdown` on SIGTERM, SIGINT and SIGHUP.
+#[cfg(unix)]
+pub fn spawn_signal_handler(app: AppHandle) {
+    use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM};
+    use signal_hook::iterator::Signals;
+
+    let Ok(mut signals) = Signals::new([SIGTERM, SIGINT, SIGHUP]) else {
+        return;
+    };
+    std::thread::spawn(move || {
+        if let Some(signal) = signals.forever().next() {
+            shutdown(&app, &format!("signal {}", signal));
+            app.exit(0);
+        }
+    });
+}
+
+#[cfg(not(unix))]
+pub fn spawn_signal_handler(_app: AppHandle) {}
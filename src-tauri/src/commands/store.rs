@@ -0,0 +1,61 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// One JSON document in the app data directory. Without a data directory
+/// nothing is found on load and saves are no-ops.
+#[derive(Debug, Clone)]
+pub struct JsonStore {
+    path: Option<PathBuf>,
+}
+
+impl JsonStore {
+    pub fn new(data_dir: Option<&Path>, file: &str) -> Self {
+        JsonStore {
+            path: data_dir.map(|dir| dir.join(file)),
+        }
+    }
+
+    /// The stored value, or `None` if the file is missing or unreadable.
+    pub fn load<T: DeserializeOwned>(&self) -> Option<T> {
+        let json = fs::read_to_string(self.path.as_ref()?).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    pub fn save<T: Serialize>(&self, value: &T) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let json = serde_json::to_vec_pretty(value).map_err(io::Error::other)?;
+        write_atomic(path, &json)
+    }
+
+    pub fn remove(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Replaces `path` through a synced temporary file and a rename, so a crash
+/// leaves either the old contents or the new ones, never a truncated file.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    {
+        let mut file = File::create(&tmp)?;
+        file.write_all(contents)?;
+        file.sync_data()?;
+    }
+    fs::rename(tmp, path)
+}
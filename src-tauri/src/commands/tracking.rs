@@ -0,0 +1,227 @@
+use super::context::{record_event, Agent, ContextEvent, ContextSession, EventType};
+use super::error::{CommandError, CommandResult};
+use super::store::JsonStore;
+use super::tray::TrayModel;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+const PAUSE_FILE: &str = "tracking_pause.json";
+const GAPS_FILE: &str = "tracking_gaps.json";
+/// Oldest gaps are dropped past this many.
+const MAX_GAPS: usize = 1000;
+const EXPIRY_CHECK: Duration = Duration::from_secs(5);
+/// Longest timed pause; anything longer is paused until resumed.
+const MAX_PAUSE_SECS: u64 = 24 * 60 * 60;
+
+/// A running privacy window. Kept on disk so a restart does not silently
+/// resume capture in the middle of a meeting.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackingPause {
+    pub started_at: DateTime<Utc>,
+    /// `None` pauses until `resume_tracking` is called.
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl TrackingPause {
+    fn expired(&self) -> bool {
+        self.until.is_some_and(|until| until <= Utc::now())
+    }
+}
+
+/// A finished pause, also recorded as a `tracking_gap` context event so
+/// analytics treat the span as deliberately untracked rather than idle time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackingGap {
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+}
+
+/// Emitted as `tracking-changed` so the frontend can stop its own capture.
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackingStatus {
+    pub paused: bool,
+    pub started_at: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+pub struct Tracking {
+    pause: Mutex<Option<TrackingPause>>,
+    gaps: Mutex<Vec<TrackingGap>>,
+    pause_store: JsonStore,
+    gaps_store: JsonStore,
+}
+
+impl Tracking {
+    pub fn load(data_dir: Option<PathBuf>) -> Self {
+        let pause_store = JsonStore::new(data_dir.as_deref(), PAUSE_FILE);
+        let gaps_store = JsonStore::new(data_dir.as_deref(), GAPS_FILE);
+        Tracking {
+            pause: Mutex::new(pause_store.load()),
+            gaps: Mutex::new(gaps_store.load().unwrap_or_default()),
+            pause_store,
+            gaps_store,
+        }
+    }
+
+    /// Whether capture is currently suppressed. Every capture source checks
+    /// this through `record_event`.
+    pub fn is_paused(&self) -> bool {
+        self.pause
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|p| !p.expired())
+    }
+
+    pub fn status(&self) -> TrackingStatus {
+        let pause = self.pause.lock().unwrap();
+        match pause.as_ref().filter(|p| !p.expired()) {
+            Some(p) => TrackingStatus {
+                paused: true,
+                started_at: Some(p.started_at),
+                until: p.until,
+            },
+            None => TrackingStatus {
+                paused: false,
+                started_at: None,
+                until: None,
+            },
+        }
+    }
+
+    pub fn gaps(&self) -> Vec<TrackingGap> {
+        self.gaps.lock().unwrap().clone()
+    }
+
+    /// Starts a pause, or extends the running one to the new `until` while
+    /// keeping its original start.
+    fn pause(&self, duration: Option<u64>) -> CommandResult<()> {
+        let until = match duration {
+            Some(secs) => Some(
+                (1..=MAX_PAUSE_SECS)
+                    .contains(&secs)
+                    .then(|| Utc::now().checked_add_signed(ChronoDuration::seconds(secs as i64)))
+                    .flatten()
+                    .ok_or_else(|| {
+                        CommandError::InvalidInput(format!(
+                            "Timed pauses last between 1 and {} seconds",
+                            MAX_PAUSE_SECS
+                        ))
+                    })?,
+            ),
+            None => None,
+        };
+        let mut pause = self.pause.lock().unwrap();
+        let started_at = match pause.as_ref() {
+            Some(p) if !p.expired() => p.started_at,
+            _ => Utc::now(),
+        };
+        let next = TrackingPause { started_at, until };
+        let _ = self.pause_store.save(&next);
+        *pause = Some(next);
+        Ok(())
+    }
+
+    /// Ends the pause and logs it as a gap. An expired pause ends at its
+    /// `until`, not at the moment the expiry was noticed.
+    fn resume(&self) -> Option<TrackingGap> {
+        let pause = self.pause.lock().unwrap().take()?;
+        let _ = self.pause_store.remove();
+        let now = Utc::now();
+        let gap = TrackingGap {
+            started_at: pause.started_at,
+            ended_at: pause.until.map_or(now, |until| until.min(now)),
+        };
+        let mut gaps = self.gaps.lock().unwrap();
+        gaps.push(gap.clone());
+        if gaps.len() > MAX_GAPS {
+            let excess = gaps.len() - MAX_GAPS;
+            gaps.drain(..excess);
+        }
+        let _ = self.gaps_store.save(&*gaps);
+        Some(gap)
+    }
+}
+
+fn announce(app: &AppHandle) {
+    let status = app.state::<Tracking>().status();
+    app.state::<TrayModel>()
+        .update(app, |s| s.tracking_paused = status.paused);
+    let _ = app.emit_all("tracking-changed", &status);
+}
+
+pub fn pause(app: &AppHandle, duration: Option<u64>) -> CommandResult<TrackingStatus> {
+    app.state::<Tracking>().pause(duration)?;
+    announce(app);
+    Ok(app.state::<Tracking>().status())
+}
+
+/// Ends the pause. The gap is recorded once capture is back on, so it goes
+/// through the event queue to the backend like any other event.
+pub fn resume(app: &AppHandle) -> Option<TrackingGap> {
+    let gap = app.state::<Tracking>().resume();
+    if let Some(gap) = &gap {
+        let session_id = app.state::<ContextSession>().id();
+        record_event(
+            app,
+            ContextEvent::new(
+                &session_id,
+                Agent::Flow,
+                EventType::TrackingGap,
+                serde_json::json!({
+                    "started_at": gap.started_at,
+                    "ended_at": gap.ended_at,
+                    "duration_secs": (gap.ended_at - gap.started_at).num_seconds(),
+                }),
+            ),
+        );
+        let _ = app.emit_all("tracking-gap", gap);
+    }
+    announce(app);
+    gap
+}
+
+/// Ends timed pauses once they run out, including one that expired while
+/// the app was not running.
+pub fn spawn_tracking_timer(app: AppHandle) {
+    announce(&app);
+    thread::spawn(move || loop {
+        let expired = app
+            .state::<Tracking>()
+            .pause
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|p| p.expired());
+        if expired {
+            resume(&app);
+        }
+        thread::sleep(EXPIRY_CHECK);
+    });
+}
+
+/// Stops all capture, for `duration` seconds or until resumed.
+#[tauri::command]
+pub fn pause_tracking(duration: Option<u64>, app: AppHandle) -> CommandResult<TrackingStatus> {
+    pause(&app, duration)
+}
+
+#[tauri::command]
+pub fn resume_tracking(app: AppHandle) -> Option<TrackingGap> {
+    resume(&app)
+}
+
+#[tauri::command]
+pub fn get_tracking_status(tracking: State<'_, Tracking>) -> TrackingStatus {
+    tracking.status()
+}
+
+#[tauri::command]
+pub fn list_tracking_gaps(tracking: State<'_, Tracking>) -> Vec<TrackingGap> {
+    tracking.gaps()
+}
@@ -1,14 +1,330 @@
-use tauri::{CustomMenuItem, Manager, SystemTray, SystemTrayEvent, SystemTrayMenu};
+use super::api::CcmClient;
+use super::context::ContextSession;
+use super::dnd::Dnd;
+use super::focus::toggle_focus;
+use super::queue::EventQueue;
+use super::shutdown::shutdown;
+use super::tracking::{self, Tracking};
+use chrono::{DateTime, Local, NaiveDate};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tauri::{
+    AppHandle, CustomMenuItem, Manager, State, SystemTray, SystemTrayEvent, SystemTrayMenu,
+    SystemTrayMenuItem,
+};
+
+/// Gaps between activity longer than this are not counted as coding time.
+const IDLE_GAP_SECS: i64 = 5 * 60;
+const FLOW_REFRESH: Duration = Duration::from_secs(60);
+const PROFILE_REFRESH: Duration = Duration::from_secs(5 * 60);
+const QUEUE_REFRESH: Duration = Duration::from_secs(10);
+/// Queued events beyond this count show the offline icon even while uploads
+/// are still succeeding.
+const QUEUE_BACKLOG_LIMIT: usize = 200;
+
+/// Which icon the tray shows. Derived from the snapshot; when several apply
+/// the most actionable one wins: paused, then offline, then stuck, then flow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrayIconState {
+    Idle,
+    Flow,
+    Stuck,
+    Paused,
+    Offline,
+}
+
+impl TrayIconState {
+    fn icon(self) -> tauri::Icon {
+        let bytes: &[u8] = match self {
+            TrayIconState::Idle => include_bytes!("../../icons/tray/idle.png"),
+            TrayIconState::Flow => include_bytes!("../../icons/tray/flow.png"),
+            TrayIconState::Stuck => include_bytes!("../../icons/tray/stuck.png"),
+            TrayIconState::Paused => include_bytes!("../../icons/tray/paused.png"),
+            TrayIconState::Offline => include_bytes!("../../icons/tray/offline.png"),
+        };
+        tauri::Icon::Raw(bytes.to_vec())
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            TrayIconState::Idle => "Idle",
+            TrayIconState::Flow => "In flow",
+            TrayIconState::Stuck => "Stuck detected",
+            TrayIconState::Paused => "Tracking paused",
+            TrayIconState::Offline => "Offline",
+        }
+    }
+}
+
+/// Everything the tray shows. Other modules change it through
+/// `TrayModel::update`, which redraws the menu.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TraySnapshot {
+    pub in_flow: Option<bool>,
+    /// Percent, 0–100.
+    pub focus_score: Option<u8>,
+    pub coding_secs_today: i64,
+    pub xp: Option<i64>,
+    pub level: Option<i64>,
+    pub streak: Option<i64>,
+    pub dnd: bool,
+    pub focus_remaining_secs: Option<i64>,
+    pub tracking_paused: bool,
+    pub stuck: bool,
+    /// The backend did not answer the last request made to it.
+    pub offline: bool,
+    pub queue_depth: usize,
+}
+
+impl TraySnapshot {
+    pub fn icon_state(&self) -> TrayIconState {
+        if self.tracking_paused {
+            TrayIconState::Paused
+        } else if self.offline || self.queue_depth > QUEUE_BACKLOG_LIMIT {
+            TrayIconState::Offline
+        } else if self.stuck {
+            TrayIconState::Stuck
+        } else if self.in_flow == Some(true) {
+            TrayIconState::Flow
+        } else {
+            TrayIconState::Idle
+        }
+    }
+
+    fn tooltip(&self) -> String {
+        let state = self.icon_state();
+        let detail = match state {
+            TrayIconState::Offline if self.queue_depth > 0 => {
+                format!("{} events waiting to upload", self.queue_depth)
+            }
+            TrayIconState::Stuck => "open PulseDev+ for suggestions".to_string(),
+            _ => self.focus_title(),
+        };
+        format!("PulseDev+ — {} · {}", state.label(), detail)
+    }
+
+    fn flow_title(&self) -> String {
+        match (self.in_flow, self.focus_score) {
+            (Some(true), Some(score)) => format!("Flow: in flow ({}%)", score),
+            (Some(true), None) => "Flow: in flow".to_string(),
+            (Some(false), _) => "Flow: not in flow".to_string(),
+            (None, _) => "Flow: unknown".to_string(),
+        }
+    }
+
+    fn coding_title(&self) -> String {
+        let minutes = self.coding_secs_today / 60;
+        format!("Today: {}h {:02}m coding", minutes / 60, minutes % 60)
+    }
+
+    fn level_title(&self) -> String {
+        match (self.level, self.xp) {
+            (Some(level), Some(xp)) => format!("Level {} · {} XP", level, xp),
+            _ => "Level: –".to_string(),
+        }
+    }
+
+    fn streak_title(&self) -> String {
+        match self.streak {
+            Some(1) => "Streak: 1 day".to_string(),
+            Some(days) => format!("Streak: {} days", days),
+            None => "Streak: –".to_string(),
+        }
+    }
+
+    fn focus_title(&self) -> String {
+        match self.focus_remaining_secs {
+            Some(secs) => format!("Focus: {:02}:{:02} left", secs / 60, secs % 60),
+            None => "Focus: off".to_string(),
+        }
+    }
+
+    fn focus_action_title(&self) -> &'static str {
+        if self.focus_remaining_secs.is_some() {
+            "Stop focus"
+        } else {
+            "Start focus (25 min)"
+        }
+    }
+
+    fn dnd_action_title(&self) -> &'static str {
+        if self.dnd {
+            "Turn Do Not Disturb off"
+        } else {
+            "Turn Do Not Disturb on"
+        }
+    }
+
+    fn tracking_action_title(&self) -> &'static str {
+        if self.tracking_paused {
+            "Resume tracking"
+        } else {
+            "Pause tracking"
+        }
+    }
+}
+
+#[derive(Default)]
+struct CodingClock {
+    day: Option<NaiveDate>,
+    last_activity: Option<DateTime<Local>>,
+}
+
+#[derive(Default)]
+pub struct TrayModel {
+    snapshot: Mutex<TraySnapshot>,
+    clock: Mutex<CodingClock>,
+    icon: Mutex<Option<TrayIconState>>,
+}
+
+impl TrayModel {
+    pub fn snapshot(&self) -> TraySnapshot {
+        self.snapshot.lock().unwrap().clone()
+    }
+
+    /// Applies `change` and redraws the tray if anything visible changed.
+    pub fn update(&self, app: &AppHandle, change: impl FnOnce(&mut TraySnapshot)) {
+        let snapshot = {
+            let mut snapshot = self.snapshot.lock().unwrap();
+            let before = snapshot.clone();
+            change(&mut snapshot);
+            if *snapshot == before {
+                return;
+            }
+            snapshot.clone()
+        };
+        redraw(app, &snapshot);
+        self.transition(app, snapshot.icon_state());
+        let _ = app.emit_all("tray-state", &snapshot);
+    }
+
+    /// Swaps the icon only on an actual state change.
+    fn transition(&self, app: &AppHandle, next: TrayIconState) {
+        let mut current = self.icon.lock().unwrap();
+        if *current == Some(next) {
+            return;
+        }
+        if app.tray_handle().set_icon(next.icon()).is_ok() {
+            *current = Some(next);
+            let _ = app.emit_all("tray-icon-state", next);
+        }
+    }
+
+    /// Counts time between consecutive bits of activity as coding time,
+    /// ignoring idle gaps and resetting at local midnight.
+    pub fn note_activity(&self, app: &AppHandle) {
+        let now = Local::now();
+        let mut clock = self.clock.lock().unwrap();
+        let new_day = clock.day != Some(now.date_naive());
+        let delta = match clock.last_activity {
+            Some(last) if !new_day => (now - last).num_seconds(),
+            _ => 0,
+        };
+        clock.day = Some(now.date_naive());
+        clock.last_activity = Some(now);
+        drop(clock);
+        let counted = if (1..=IDLE_GAP_SECS).contains(&delta) {
+            delta
+        } else {
+            0
+        };
+        if new_day || counted > 0 {
+            self.update(app, |s| {
+                if new_day {
+                    s.coding_secs_today = 0;
+                }
+                s.coding_secs_today += counted;
+            });
+        }
+    }
+}
+
+fn redraw(app: &AppHandle, snapshot: &TraySnapshot) {
+    let tray = app.tray_handle();
+    let titles = [
+        ("flow", snapshot.flow_title()),
+        ("coding", snapshot.coding_title()),
+        ("level", snapshot.level_title()),
+        ("streak", snapshot.streak_title()),
+        ("focus", snapshot.focus_title()),
+        ("focus_toggle", snapshot.focus_action_title().to_string()),
+        ("dnd_toggle", snapshot.dnd_action_title().to_string()),
+        (
+            "tracking_toggle",
+            snapshot.tracking_action_title().to_string(),
+        ),
+    ];
+    for (id, title) in titles {
+        let _ = tray.get_item(id).set_title(title);
+    }
+    let _ = tray.set_tooltip(&snapshot.tooltip());
+}
 
 pub fn create_tray() -> SystemTray {
-    let show = CustomMenuItem::new("show".to_string(), "Show");
-    let hide = CustomMenuItem::new("hide".to_string(), "Hide");
-    let quit = CustomMenuItem::new("quit".to_string(), "Quit");
+    let initial = TraySnapshot::default();
+    let status = |id: &str, title: String| CustomMenuItem::new(id.to_string(), title).disabled();
+    let action = |id: &str, title: &str| CustomMenuItem::new(id.to_string(), title);
     let menu = SystemTrayMenu::new()
-        .add_item(show)
-        .add_item(hide)
-        .add_item(quit);
-    SystemTray::new().with_menu(menu)
+        .add_item(status("flow", initial.flow_title()))
+        .add_item(status("coding", initial.coding_title()))
+        .add_item(status("level", initial.level_title()))
+        .add_item(status("streak", initial.streak_title()))
+        .add_item(status("focus", initial.focus_title()))
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(action("focus_toggle", initial.focus_action_title()))
+        .add_item(action("dnd_toggle", initial.dnd_action_title()))
+        .add_item(action("tracking_toggle", initial.tracking_action_title()))
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(action("show", "Show"))
+        .add_item(action("hide", "Hide"))
+        .add_item(action("quit", "Quit"));
+    SystemTray::new()
+        .with_icon(TrayIconState::Idle.icon())
+        .with_tooltip(&initial.tooltip())
+        .with_menu(menu)
+}
+
+/// Polls the backend for flow state and gamification stats so the tray stays
+/// current while the main window is closed.
+pub fn spawn_tray_refresher(app: AppHandle) {
+    let flow_app = app.clone();
+    thread::spawn(move || loop {
+        let session_id = flow_app.state::<ContextSession>().id();
+        if let Ok(flow) = flow_app.state::<Arc<CcmClient>>().flow_state(&session_id) {
+            flow_app.state::<TrayModel>().update(&flow_app, |s| {
+                s.in_flow = Some(flow.in_flow);
+                s.focus_score = Some(flow.focus_percent());
+                // Getting back into flow resolves a stuck state.
+                s.stuck = s.stuck && !flow.in_flow;
+            });
+        }
+        thread::sleep(FLOW_REFRESH);
+    });
+    let queue_app = app.clone();
+    thread::spawn(move || loop {
+        let status = queue_app.state::<Arc<EventQueue>>().status();
+        // Every backend call, uploads included, goes through the one client.
+        let reachable = queue_app.state::<Arc<CcmClient>>().reachable();
+        queue_app.state::<TrayModel>().update(&queue_app, |s| {
+            s.offline = !reachable;
+            s.queue_depth = status.depth;
+        });
+        thread::sleep(QUEUE_REFRESH);
+    });
+    thread::spawn(move || loop {
+        let session_id = app.state::<ContextSession>().id();
+        if let Ok(profile) = app.state::<Arc<CcmClient>>().profile(&session_id) {
+            app.state::<TrayModel>().update(&app, |s| {
+                s.xp = Some(profile.total_xp);
+                s.level = Some(profile.level);
+                s.streak = Some(profile.current_streak);
+            });
+        }
+        thread::sleep(PROFILE_REFRESH);
+    });
 }
 
 pub fn handle_tray_event(app: &tauri::AppHandle, event: SystemTrayEvent) {
@@ -22,11 +338,39 @@ pub fn handle_tray_event(app: &tauri::AppHandle, event: SystemTrayEvent) {
                 let window = app.get_window("main").unwrap();
                 window.hide().unwrap();
             }
+            "focus_toggle" => toggle_focus(app, "Started from tray"),
+            "dnd_toggle" => {
+                let dnd = app.state::<Dnd>();
+                if let Ok(enabled) = dnd.is_enabled() {
+                    if dnd.set_enabled(!enabled).is_ok() {
+                        app.state::<TrayModel>().update(app, |s| s.dnd = !enabled);
+                    }
+                }
+            }
+            "tracking_toggle" => {
+                if app.state::<Tracking>().is_paused() {
+                    tracking::resume(app);
+                } else {
+                    let _ = tracking::pause(app, None);
+                }
+            }
             "quit" => {
-                std::process::exit(0);
+                shutdown(app, "tray_quit");
+                app.exit(0);
             }
             _ => {}
         },
         _ => {}
     }
 }
+
+#[tauri::command]
+pub fn get_tray_state(tray: State<'_, TrayModel>) -> TraySnapshot {
+    tray.snapshot()
+}
+
+/// Lets the dashboard mirror the backend's stuck detection on the tray.
+#[tauri::command]
+pub fn set_stuck_state(stuck: bool, app: AppHandle, tray: State<'_, TrayModel>) {
+    tray.update(&app, |s| s.stuck = stuck);
+}
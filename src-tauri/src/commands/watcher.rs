@@ -0,0 +1,423 @@
+use super::context::{record_event, Agent, ContextEvent, ContextSession, EventType};
+use super::error::CommandResult;
+use super::scope::WorkspaceScope;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::WalkBuilder;
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+const DEBOUNCE: Duration = Duration::from_millis(500);
+const DEFAULT_IGNORES: &[&str] = &[".git/", "target/", "node_modules/"];
+const GITIGNORE: &str = ".gitignore";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Change {
+    Created,
+    Modified,
+    Deleted,
+}
+
+struct Pending {
+    change: Change,
+    last_seen: Instant,
+}
+
+/// A watched root with the `.gitignore` of every directory under it, each
+/// applying to its own subtree the way git reads them.
+struct Root {
+    path: PathBuf,
+    defaults: Gitignore,
+    nested: HashMap<PathBuf, Gitignore>,
+}
+
+impl Root {
+    fn new(path: PathBuf) -> Self {
+        let mut builder = GitignoreBuilder::new(&path);
+        for pattern in DEFAULT_IGNORES {
+            let _ = builder.add_line(None, pattern);
+        }
+        let defaults = builder.build().unwrap_or_else(|_| Gitignore::empty());
+        // The walk itself honours the files it finds, so ignored trees such
+        // as `node_modules` are never descended into.
+        let nested = WalkBuilder::new(&path)
+            .hidden(false)
+            .git_global(false)
+            .git_exclude(false)
+            .require_git(false)
+            .filter_entry(|entry| entry.file_name() != ".git")
+            .build()
+            .flatten()
+            .filter(|entry| entry.file_name() == GITIGNORE)
+            .filter_map(|entry| {
+                let dir = entry.path().parent()?.to_path_buf();
+                let ignore = load_gitignore(&dir)?;
+                Some((dir, ignore))
+            })
+            .collect();
+        Root {
+            path,
+            defaults,
+            nested,
+        }
+    }
+
+    /// Picks up a `.gitignore` created, edited or removed in `dir`.
+    fn reload(&mut self, dir: &Path) {
+        match load_gitignore(dir) {
+            Some(ignore) => self.nested.insert(dir.to_path_buf(), ignore),
+            None => self.nested.remove(dir),
+        };
+    }
+
+    /// The deepest `.gitignore` with an opinion on `path` decides, so a
+    /// nested `!pattern` can re-include what a parent excluded.
+    fn is_ignored(&self, path: &Path) -> bool {
+        let is_dir = path.is_dir();
+        if self
+            .defaults
+            .matched_path_or_any_parents(path, is_dir)
+            .is_ignore()
+        {
+            return true;
+        }
+        for dir in path
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(&self.path))
+        {
+            if let Some(ignore) = self.nested.get(dir) {
+                let matched = ignore.matched_path_or_any_parents(path, is_dir);
+                if matched.is_ignore() {
+                    return true;
+                }
+                if matched.is_whitelist() {
+                    return false;
+                }
+            }
+        }
+        false
+    }
+}
+
+fn load_gitignore(dir: &Path) -> Option<Gitignore> {
+    let file = dir.join(GITIGNORE);
+    if !file.is_file() {
+        return None;
+    }
+    let mut builder = GitignoreBuilder::new(dir);
+    builder.add(file);
+    builder.build().ok()
+}
+
+fn is_ignored(roots: &[Root], path: &Path) -> bool {
+    roots
+        .iter()
+        .filter(|root| path.starts_with(&root.path))
+        .any(|root| root.is_ignored(path))
+}
+
+fn root_of<'a>(roots: &'a [Root], path: &Path) -> Option<&'a Path> {
+    roots
+        .iter()
+        .map(|root| root.path.as_path())
+        .filter(|root| path.starts_with(root))
+        .max_by_key(|root| root.as_os_str().len())
+}
+
+/// Folds a new change into whatever is already pending for the same path so
+/// an editor's write-rename-chmod burst surfaces as a single event.
+fn merge(previous: Option<Change>, next: Change) -> Option<Change> {
+    match (previous, next) {
+        (None, next) => Some(next),
+        (Some(Change::Created), Change::Modified) => Some(Change::Created),
+        (Some(Change::Created), Change::Deleted) => None,
+        (Some(Change::Deleted), Change::Created) => Some(Change::Modified),
+        (Some(_), next) => Some(next),
+    }
+}
+
+/// The live inotify (or platform equivalent) watcher over the granted roots.
+/// Dropping it closes the event channel, which ends the debounce thread.
+#[derive(Default)]
+pub struct FileWatcher {
+    inner: Mutex<Option<(RecommendedWatcher, JoinHandle<()>)>>,
+}
+
+impl FileWatcher {
+    pub fn start(&self, app: AppHandle, roots: Vec<PathBuf>) -> notify::Result<Vec<String>> {
+        let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+        let mut watcher = notify::recommended_watcher(move |res| {
+            let _ = tx.send(res);
+        })?;
+        let roots: Vec<Root> = roots
+            .into_iter()
+            .filter(|p| p.is_dir())
+            .map(Root::new)
+            .collect();
+        for root in &roots {
+            watcher.watch(&root.path, RecursiveMode::Recursive)?;
+        }
+        let watched = roots.iter().map(|r| r.path.display().to_string()).collect();
+
+        let debouncer = thread::spawn(move || debounce_loop(app, roots, rx));
+        *self.inner.lock().unwrap() = Some((watcher, debouncer));
+        Ok(watched)
+    }
+
+    /// Stops watching and waits for the debounce thread to emit whatever was
+    /// still pending, so no change is lost on shutdown.
+    pub fn stop(&self) {
+        let running = self.inner.lock().unwrap().take();
+        if let Some((watcher, debouncer)) = running {
+            drop(watcher);
+            let _ = debouncer.join();
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.inner.lock().unwrap().is_some()
+    }
+}
+
+fn debounce_loop(app: AppHandle, mut roots: Vec<Root>, rx: mpsc::Receiver<notify::Result<Event>>) {
+    let mut pending: HashMap<PathBuf, Pending> = HashMap::new();
+    loop {
+        match rx.recv_timeout(DEBOUNCE) {
+            Ok(Ok(event)) => {
+                reload_gitignores(&mut roots, &event.paths);
+                handle_event(&app, &roots, &mut pending, event)
+            }
+            Ok(Err(_)) => {}
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                flush(&app, &roots, &mut pending, Duration::ZERO);
+                return;
+            }
+        }
+        flush(&app, &roots, &mut pending, DEBOUNCE);
+    }
+}
+
+fn reload_gitignores(roots: &mut [Root], paths: &[PathBuf]) {
+    for path in paths
+        .iter()
+        .filter(|p| p.file_name() == Some(OsStr::new(GITIGNORE)))
+    {
+        let Some(dir) = path.parent() else {
+            continue;
+        };
+        for root in roots.iter_mut().filter(|r| dir.starts_with(&r.path)) {
+            root.reload(dir);
+        }
+    }
+}
+
+fn handle_event(
+    app: &AppHandle,
+    roots: &[Root],
+    pending: &mut HashMap<PathBuf, Pending>,
+    event: Event,
+) {
+    if let Some(renamed) = track(roots, pending, event) {
+        emit(app, EventType::FileRenamed, renamed);
+    }
+}
+
+/// Folds `event` into `pending`. A paired rename is not debounced; its
+/// payload is returned to be emitted at once.
+fn track(
+    roots: &[Root],
+    pending: &mut HashMap<PathBuf, Pending>,
+    event: Event,
+) -> Option<serde_json::Value> {
+    let change = match event.kind {
+        // The two halves of a rename arrive first, on Linux as From and To
+        // before the paired Both; they are treated as a delete and a create
+        // until the pair arrives and replaces them.
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+            let (from, to) = (&event.paths[0], &event.paths[1]);
+            if is_ignored(roots, from) && is_ignored(roots, to) {
+                return None;
+            }
+            pending.remove(from);
+            pending.remove(to);
+            return Some(serde_json::json!({
+                "from": from.display().to_string(),
+                "to": to.display().to_string(),
+                "root": root_of(roots, to).map(|r| r.display().to_string()),
+            }));
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => Change::Deleted,
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => Change::Created,
+        EventKind::Modify(ModifyKind::Metadata(_)) => return None,
+        EventKind::Create(_) => Change::Created,
+        EventKind::Modify(_) => Change::Modified,
+        EventKind::Remove(_) => Change::Deleted,
+        _ => return None,
+    };
+    for path in event.paths {
+        if is_ignored(roots, &path) {
+            continue;
+        }
+        let previous = pending.get(&path).map(|p| p.change);
+        match merge(previous, change) {
+            Some(change) => {
+                pending.insert(
+                    path,
+                    Pending {
+                        change,
+                        last_seen: Instant::now(),
+                    },
+                );
+            }
+            None => {
+                pending.remove(&path);
+            }
+        }
+    }
+    None
+}
+
+fn flush(
+    app: &AppHandle,
+    roots: &[Root],
+    pending: &mut HashMap<PathBuf, Pending>,
+    quiet_for: Duration,
+) {
+    let settled: Vec<PathBuf> = pending
+        .iter()
+        .filter(|(_, p)| p.last_seen.elapsed() >= quiet_for)
+        .map(|(path, _)| path.clone())
+        .collect();
+    for path in settled {
+        if let Some(p) = pending.remove(&path) {
+            let event_type = match p.change {
+                Change::Created => EventType::FileCreated,
+                Change::Modified => EventType::FileModified,
+                Change::Deleted => EventType::FileDeleted,
+            };
+            emit(
+                app,
+                event_type,
+                serde_json::json!({
+                    "path": path.display().to_string(),
+                    "root": root_of(roots, &path).map(|r| r.display().to_string()),
+                }),
+            );
+        }
+    }
+}
+
+fn emit(app: &AppHandle, event_type: EventType, payload: serde_json::Value) {
+    let session_id = app.state::<ContextSession>().id();
+    record_event(
+        app,
+        ContextEvent::new(&session_id, Agent::File, event_type, payload),
+    );
+}
+
+/// (Re)starts the watcher over the current workspace grants. The frontend
+/// calls this again after granting or revoking a workspace.
+#[tauri::command]
+pub fn start_file_watcher(
+    app: AppHandle,
+    scope: State<'_, WorkspaceScope>,
+    watcher: State<'_, FileWatcher>,
+) -> CommandResult<Vec<String>> {
+    watcher.stop();
+    Ok(watcher.start(app, scope.roots())?)
+}
+
+#[tauri::command]
+pub fn stop_file_watcher(watcher: State<'_, FileWatcher>) {
+    watcher.stop();
+}
+
+#[tauri::command]
+pub fn file_watcher_running(watcher: State<'_, FileWatcher>) -> bool {
+    watcher.is_running()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_nested_gitignores_apply_to_their_subtree() {
+        let tmp = std::env::temp_dir().join(format!("pulsedev-watch-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(tmp.join("app/build")).unwrap();
+        fs::create_dir_all(tmp.join("lib")).unwrap();
+        fs::write(tmp.join(".gitignore"), "*.log\n").unwrap();
+        fs::write(tmp.join("app/.gitignore"), "build/\n!keep.log\n").unwrap();
+
+        let root = Root::new(tmp.clone());
+        assert!(root.is_ignored(&tmp.join("debug.log")));
+        assert!(root.is_ignored(&tmp.join("app/build/out.js")));
+        assert!(!root.is_ignored(&tmp.join("app/keep.log")));
+        assert!(root.is_ignored(&tmp.join("app/other.log")));
+        assert!(!root.is_ignored(&tmp.join("lib/build/out.js")));
+        assert!(root.is_ignored(&tmp.join("node_modules/x/index.js")));
+        assert!(!root.is_ignored(&tmp.join("app/main.rs")));
+        fs::remove_dir_all(tmp).unwrap();
+    }
+
+    #[test]
+    fn test_reload_picks_up_new_gitignore() {
+        let tmp = std::env::temp_dir().join(format!("pulsedev-watch-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(tmp.join("docs")).unwrap();
+        let mut root = Root::new(tmp.clone());
+        assert!(!root.is_ignored(&tmp.join("docs/draft.md")));
+
+        fs::write(tmp.join("docs/.gitignore"), "draft.md\n").unwrap();
+        reload_gitignores(
+            std::slice::from_mut(&mut root),
+            &[tmp.join("docs/.gitignore")],
+        );
+        assert!(root.is_ignored(&tmp.join("docs/draft.md")));
+
+        fs::remove_file(tmp.join("docs/.gitignore")).unwrap();
+        root.reload(&tmp.join("docs"));
+        assert!(!root.is_ignored(&tmp.join("docs/draft.md")));
+        fs::remove_dir_all(tmp).unwrap();
+    }
+
+    #[test]
+    fn test_rename_halves_collapse_into_one_rename() {
+        let tmp = std::env::temp_dir().join(format!("pulsedev-watch-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&tmp).unwrap();
+        let roots = vec![Root::new(tmp.clone())];
+        let (from, to) = (tmp.join("old.rs"), tmp.join("new.rs"));
+        let rename = |mode, paths: Vec<PathBuf>| {
+            paths.into_iter().fold(
+                Event::new(EventKind::Modify(ModifyKind::Name(mode))),
+                Event::add_path,
+            )
+        };
+
+        let mut pending = HashMap::new();
+        let emitted: Vec<serde_json::Value> = [
+            rename(RenameMode::From, vec![from.clone()]),
+            rename(RenameMode::To, vec![to.clone()]),
+            rename(RenameMode::Both, vec![from.clone(), to.clone()]),
+        ]
+        .into_iter()
+        .filter_map(|event| track(&roots, &mut pending, event))
+        .collect();
+
+        assert_eq!(emitted.len(), 1);
+        assert_eq!(emitted[0]["from"], from.display().to_string());
+        assert_eq!(emitted[0]["to"], to.display().to_string());
+        // Nothing is left to flush as a delete or a create.
+        assert!(pending.is_empty());
+        fs::remove_dir_all(tmp).unwrap();
+    }
+}
@@ -0,0 +1,160 @@
+use super::error::CommandResult;
+use super::store::JsonStore;
+use super::tray::{TrayModel, TraySnapshot};
+use super::window::restore_window;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, WindowBuilder, WindowUrl};
+
+pub const WIDGET_LABEL: &str = "widget";
+const WIDGET_FILE: &str = "widget.json";
+const WIDGET_ROUTE: &str = "index.html#/widget";
+const WIDGET_WIDTH: f64 = 300.0;
+const WIDGET_HEIGHT: f64 = 132.0;
+
+/// Settings that outlive the widget window itself. Its geometry is kept with
+/// the other windows in `WindowStates`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WidgetSettings {
+    /// Reopen the widget on launch.
+    pub open: bool,
+    /// Keep the widget above other windows.
+    pub pinned: bool,
+}
+
+impl Default for WidgetSettings {
+    fn default() -> Self {
+        WidgetSettings {
+            open: false,
+            pinned: true,
+        }
+    }
+}
+
+/// Everything the widget shows, emitted as `widget-state` to every window
+/// whenever the task or pin changes. Live updates to the tray fields arrive
+/// separately as `tray-state`.
+#[derive(Debug, Clone, Serialize)]
+pub struct WidgetState {
+    pub open: bool,
+    pub pinned: bool,
+    pub task: Option<String>,
+    pub tray: TraySnapshot,
+}
+
+pub struct Widget {
+    settings: Mutex<WidgetSettings>,
+    task: Mutex<Option<String>>,
+    store: JsonStore,
+}
+
+impl Widget {
+    pub fn load(data_dir: Option<PathBuf>) -> Self {
+        let store = JsonStore::new(data_dir.as_deref(), WIDGET_FILE);
+        let settings = store.load().unwrap_or_default();
+        Widget {
+            settings: Mutex::new(settings),
+            task: Mutex::new(None),
+            store,
+        }
+    }
+
+    pub fn settings(&self) -> WidgetSettings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    fn update(&self, change: impl FnOnce(&mut WidgetSettings)) {
+        let mut settings = self.settings.lock().unwrap();
+        change(&mut settings);
+        let _ = self.store.save(&*settings);
+    }
+}
+
+fn widget_state(app: &AppHandle) -> WidgetState {
+    let widget = app.state::<Widget>();
+    let settings = widget.settings();
+    WidgetState {
+        open: app.get_window(WIDGET_LABEL).is_some(),
+        pinned: settings.pinned,
+        task: widget.task.lock().unwrap().clone(),
+        tray: app.state::<TrayModel>().snapshot(),
+    }
+}
+
+fn broadcast(app: &AppHandle) -> WidgetState {
+    let state = widget_state(app);
+    let _ = app.emit_all("widget-state", &state);
+    state
+}
+
+/// Opens the widget, or focuses it if it is already open.
+pub fn open_widget(app: &AppHandle) -> CommandResult<()> {
+    if let Some(window) = app.get_window(WIDGET_LABEL) {
+        window.show()?;
+        window.set_focus()?;
+        return Ok(());
+    }
+    let pinned = app.state::<Widget>().settings().pinned;
+    let window = WindowBuilder::new(app, WIDGET_LABEL, WindowUrl::App(WIDGET_ROUTE.into()))
+        .title("PulseDev+ Focus")
+        .inner_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+        .resizable(false)
+        .decorations(false)
+        .skip_taskbar(true)
+        .always_on_top(pinned)
+        .visible(false)
+        .build()?;
+    restore_window(&window);
+    window.show()?;
+    app.state::<Widget>().update(|s| s.open = true);
+    Ok(())
+}
+
+/// Reopens the widget if it was open when the app last quit.
+pub fn resume_widget(app: &AppHandle) {
+    if app.state::<Widget>().settings().open {
+        let _ = open_widget(app);
+    }
+}
+
+#[tauri::command]
+pub fn open_focus_widget(app: AppHandle) -> CommandResult<WidgetState> {
+    open_widget(&app)?;
+    Ok(broadcast(&app))
+}
+
+#[tauri::command]
+pub fn close_focus_widget(app: AppHandle) -> CommandResult<WidgetState> {
+    if let Some(window) = app.get_window(WIDGET_LABEL) {
+        window.close()?;
+    }
+    app.state::<Widget>().update(|s| s.open = false);
+    let mut state = widget_state(&app);
+    // The window is only gone once the close has been processed.
+    state.open = false;
+    let _ = app.emit_all("widget-state", &state);
+    Ok(state)
+}
+
+#[tauri::command]
+pub fn pin_focus_widget(pinned: bool, app: AppHandle) -> CommandResult<WidgetState> {
+    if let Some(window) = app.get_window(WIDGET_LABEL) {
+        window.set_always_on_top(pinned)?;
+    }
+    app.state::<Widget>().update(|s| s.pinned = pinned);
+    Ok(broadcast(&app))
+}
+
+/// Lets the main window tell the widget what the user is working on.
+#[tauri::command]
+pub fn set_widget_task(task: Option<String>, app: AppHandle) -> WidgetState {
+    *app.state::<Widget>().task.lock().unwrap() = task;
+    broadcast(&app)
+}
+
+#[tauri::command]
+pub fn get_widget_state(app: AppHandle) -> WidgetState {
+    widget_state(&app)
+}
@@ -1,26 +1,198 @@
-use tauri::Window;
+use super::error::CommandResult;
+use super::store::JsonStore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{Manager, Monitor, PhysicalPosition, PhysicalSize, State, Window, WindowEvent};
 
 #[tauri::command]
-pub fn minimize_window(window: Window) {
-    let _ = window.minimize();
+pub fn minimize_window(window: Window) -> CommandResult<()> {
+    Ok(window.minimize()?)
 }
 
 #[tauri::command]
-pub fn maximize_window(window: Window) {
-    let _ = window.maximize();
+pub fn maximize_window(window: Window) -> CommandResult<()> {
+    Ok(window.maximize()?)
 }
 
 #[tauri::command]
-pub fn close_window(window: Window) {
-    let _ = window.close();
+pub fn close_window(window: Window) -> CommandResult<()> {
+    Ok(window.close()?)
 }
 
 #[tauri::command]
-pub fn show_window(window: Window) {
-    let _ = window.show();
+pub fn show_window(window: Window) -> CommandResult<()> {
+    Ok(window.show()?)
 }
 
 #[tauri::command]
-pub fn hide_window(window: Window) {
-    let _ = window.hide();
+pub fn hide_window(window: Window) -> CommandResult<()> {
+    Ok(window.hide()?)
+}
+
+const WINDOW_STATE_FILE: &str = "window_state.json";
+/// How much of a restored window's top edge must land on a monitor for the
+/// saved position to be trusted; enough to grab the title bar.
+const MIN_VISIBLE: i32 = 64;
+
+/// Geometry of one window, in physical pixels, plus the route the frontend
+/// last reported for it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WindowState {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub monitor: Option<String>,
+    pub maximized: bool,
+    pub route: Option<String>,
+}
+
+/// Saved state of every window, keyed by window label.
+pub struct WindowStates {
+    states: Mutex<HashMap<String, WindowState>>,
+    store: JsonStore,
+}
+
+impl WindowStates {
+    pub fn load(data_dir: Option<PathBuf>) -> Self {
+        let store = JsonStore::new(data_dir.as_deref(), WINDOW_STATE_FILE);
+        let states = store.load().unwrap_or_default();
+        WindowStates {
+            states: Mutex::new(states),
+            store,
+        }
+    }
+
+    pub fn get(&self, label: &str) -> Option<WindowState> {
+        self.states.lock().unwrap().get(label).cloned()
+    }
+
+    pub fn persist(&self) {
+        let _ = self.store.save(&*self.states.lock().unwrap());
+    }
+
+    /// Records the window's current geometry. Size and position are left
+    /// alone while maximized or minimized so that un-maximizing after a
+    /// restart returns to the normal bounds.
+    pub fn capture(&self, window: &Window) {
+        let maximized = window.is_maximized().unwrap_or(false);
+        let minimized = window.is_minimized().unwrap_or(false);
+        let mut states = self.states.lock().unwrap();
+        let state = states.entry(window.label().to_string()).or_default();
+        state.maximized = maximized;
+        if maximized || minimized {
+            return;
+        }
+        if let (Ok(position), Ok(size)) = (window.outer_position(), window.outer_size()) {
+            state.x = position.x;
+            state.y = position.y;
+            state.width = size.width;
+            state.height = size.height;
+        }
+        if let Ok(Some(monitor)) = window.current_monitor() {
+            state.monitor = monitor.name().cloned();
+        }
+    }
+
+    fn set_route(&self, label: &str, route: String) {
+        let mut states = self.states.lock().unwrap();
+        states.entry(label.to_string()).or_default().route = Some(route);
+    }
+}
+
+/// Puts `window` back where it was last closed, on the monitor it was on.
+/// If that monitor is still connected but the saved spot is no longer on it,
+/// the window is centred on that monitor; if the monitor is gone and the
+/// spot is off every screen, it is centred on the primary monitor. Either
+/// way it is shrunk to fit if needed.
+pub fn restore_window(window: &Window) {
+    let Some(state) = window.state::<WindowStates>().get(window.label()) else {
+        return;
+    };
+    if state.width == 0 || state.height == 0 {
+        if state.maximized {
+            let _ = window.maximize();
+        }
+        return;
+    }
+    let visible_on = |m: &Monitor| {
+        let (origin, size) = (m.position(), m.size());
+        let right = origin.x + size.width as i32;
+        let bottom = origin.y + size.height as i32;
+        state.x + state.width as i32 - MIN_VISIBLE >= origin.x
+            && state.x + MIN_VISIBLE <= right
+            && state.y >= origin.y
+            && state.y + MIN_VISIBLE <= bottom
+    };
+    let monitors = window.available_monitors().unwrap_or_default();
+    let saved_monitor = state
+        .monitor
+        .as_ref()
+        .and_then(|name| monitors.iter().find(|m| m.name() == Some(name)));
+
+    let target = match saved_monitor {
+        Some(monitor) if visible_on(monitor) => None,
+        Some(monitor) => Some(monitor.clone()),
+        None if monitors.iter().any(visible_on) => None,
+        None => window.primary_monitor().ok().flatten(),
+    };
+    match target {
+        None => {
+            let _ = window.set_size(PhysicalSize::new(state.width, state.height));
+            let _ = window.set_position(PhysicalPosition::new(state.x, state.y));
+        }
+        Some(monitor) => {
+            let area = monitor.size();
+            let width = state.width.min(area.width);
+            let height = state.height.min(area.height);
+            let _ = window.set_size(PhysicalSize::new(width, height));
+            let _ = window.set_position(PhysicalPosition::new(
+                monitor.position().x + (area.width - width) as i32 / 2,
+                monitor.position().y + (area.height - height) as i32 / 2,
+            ));
+        }
+    }
+    if state.maximized {
+        let _ = window.maximize();
+    }
+}
+
+/// Keeps the saved state current as the window moves, and writes it out when
+/// the window goes away.
+pub fn track_window_state(window: &Window, event: &WindowEvent) {
+    let states = window.state::<WindowStates>();
+    match event {
+        WindowEvent::Moved(_) | WindowEvent::Resized(_) => states.capture(window),
+        WindowEvent::CloseRequested { .. } => {
+            states.capture(window);
+            states.persist();
+        }
+        WindowEvent::Destroyed => states.persist(),
+        _ => {}
+    }
+}
+
+/// Closing the main window only hides it; the app keeps running in the tray
+/// until Quit there or a signal ends it.
+pub fn hide_main_on_close(window: &Window, event: &WindowEvent) {
+    if let WindowEvent::CloseRequested { api, .. } = event {
+        if window.label() == "main" {
+            api.prevent_close();
+            let _ = window.hide();
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_window_state(window: Window, states: State<'_, WindowStates>) -> Option<WindowState> {
+    states.get(window.label())
+}
+
+/// Called by the frontend on navigation so the next launch reopens the same
+/// view.
+#[tauri::command]
+pub fn set_last_route(route: String, window: Window, states: State<'_, WindowStates>) {
+    states.set_route(window.label(), route);
 }
@@ -2,29 +2,154 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod commands;
-use commands::{dnd::*, filesystem::*, notifications::*, tray::*, window::*};
-use tauri::{Manager, SystemTrayEvent};
+use commands::{
+    api::*, autocommit::*, branchname::*, capture::*, commitmsg::*, context::*, deeplink::*,
+    dnd::*, filesystem::*, focus::*, gitwatch::*, inbox::*, instance::*, notifications::*,
+    policy::*, queue::*, scope::*, shortcuts::*, shutdown::*, tracking::*, tray::*, watcher::*,
+    widget::*, window::*,
+};
+use tauri::{Manager, RunEvent, SystemTrayEvent};
 
 fn main() {
+    // A second launch only forwards its arguments to the running instance.
+    let Some(instance) = claim_instance(&InstanceArgs::current()) else {
+        return;
+    };
     let tray = create_tray();
     tauri::Builder::default()
         .system_tray(tray)
+        .manage(instance)
+        .manage(PendingDeepLink::default())
+        .manage(ContextSession::new())
+        .manage(Dnd::detect())
+        .manage(Notifier::connect())
+        .manage(TrayModel::default())
+        .setup(|app| {
+            let data_dir = app.path_resolver().app_data_dir();
+            let client = CcmClient::load(data_dir.clone());
+            let queue = EventQueue::open(data_dir.clone(), client.clone());
+            queue.spawn_uploader();
+            app.manage(client);
+            app.manage(queue);
+            app.manage(Tracking::load(data_dir.clone()));
+            app.manage(WindowStates::load(data_dir.clone()));
+            if let Some(window) = app.get_window("main") {
+                restore_window(&window);
+            }
+            app.manage(Widget::load(data_dir.clone()));
+            resume_widget(&app.handle());
+            app.manage(Shortcuts::load(data_dir.clone()));
+            register_shortcuts(&app.handle());
+            app.manage(NotificationPolicy::load(data_dir.clone()));
+            app.manage(Inbox::load(data_dir.clone()));
+            app.manage(Focus::load(data_dir.clone()));
+            resume_focus(&app.handle());
+            spawn_dnd_watcher(app.handle());
+            spawn_action_listener(app.handle());
+            spawn_digest_timer(app.handle());
+            spawn_tray_refresher(app.handle());
+            spawn_tracking_timer(app.handle());
+            spawn_instance_listener(app.handle());
+            handle_deep_link_args(&app.handle(), &InstanceArgs::current().args);
+            spawn_signal_handler(app.handle());
+            let scope = WorkspaceScope::load(data_dir.clone());
+            let watcher = FileWatcher::default();
+            let _ = watcher.start(app.handle(), scope.roots());
+            let git_watcher = GitWatcher::default();
+            let _ = git_watcher.start(app.handle(), scope.roots());
+            app.manage(scope);
+            app.manage(watcher);
+            app.manage(git_watcher);
+            app.manage(AutoCommits::load(data_dir.clone()));
+            app.manage(BranchNamingConfig::load(data_dir));
+            Ok(())
+        })
+        .on_window_event(|event| {
+            track_window_state(event.window(), event.event());
+            hide_main_on_close(event.window(), event.event());
+        })
         .on_system_tray_event(|app, event| {
             handle_tray_event(app, event);
         })
         .invoke_handler(tauri::generate_handler![
             send_notification,
+            send_rich_notification,
+            close_notification,
+            get_notification_policy,
+            set_notification_policy,
+            flush_notification_digest,
+            list_notifications,
+            mark_read,
+            clear_notifications,
             pick_file,
             read_file,
             write_file,
+            grant_workspace,
+            list_workspaces,
+            revoke_workspace,
+            get_session_id,
+            set_session_id,
+            start_file_watcher,
+            stop_file_watcher,
+            file_watcher_running,
+            event_queue_status,
+            flush_event_queue,
+            get_api_config,
+            set_api_config,
+            get_energy_score,
+            award_xp,
+            sync_gamification_session,
+            get_achievements,
+            get_leaderboard,
+            track_activity,
+            get_gamification_dashboard,
             get_dnd_status,
             set_dnd_status,
+            get_dnd_backend,
+            start_focus_session,
+            cancel_focus_session,
+            get_focus_session,
+            get_tray_state,
+            set_stuck_state,
+            pause_tracking,
+            resume_tracking,
+            get_tracking_status,
+            list_tracking_gaps,
             minimize_window,
             maximize_window,
             close_window,
             show_window,
-            hide_window
+            hide_window,
+            get_window_state,
+            set_last_route,
+            open_focus_widget,
+            close_focus_widget,
+            pin_focus_widget,
+            set_widget_task,
+            get_widget_state,
+            get_shortcuts,
+            set_shortcuts,
+            open_quick_capture_window,
+            submit_quick_capture,
+            take_deep_link,
+            parse_link,
+            start_git_watcher,
+            stop_git_watcher,
+            auto_commit_dry_run,
+            execute_auto_commit,
+            undo_auto_commit,
+            draft_commit_message,
+            suggest_branch_names,
+            create_branch,
+            get_branch_naming,
+            set_branch_naming
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| match event {
+            // The tray keeps the app alive with every window closed.
+            RunEvent::ExitRequested { api, .. } => api.prevent_exit(),
+            RunEvent::Exit => shutdown(app, "exit"),
+            _ => {}
+        });
 }
@@ -4,15 +4,20 @@ use tauri_test::{assert, Builder};
 #[test]
 fn test_get_dnd_status() {
     let mut app = Builder::new().build();
-    let result = app.command("get_dnd_status", None::<()>);
-    assert!(result.is_ok());
+    // Hosts without a DND backend, such as headless CI, report Unsupported.
+    match app.command("get_dnd_status", None::<()>) {
+        Ok(enabled) => assert!(enabled.is_boolean()),
+        Err(error) => assert_eq!(error["code"], "Unsupported"),
+    }
 }
 
 #[test]
 fn test_set_dnd_status() {
     let mut app = Builder::new().build();
-    let result = app.command("set_dnd_status", Some(serde_json::json!({"enabled": true})));
-    assert!(result.is_ok());
+    match app.command("set_dnd_status", Some(serde_json::json!({"enabled": true}))) {
+        Ok(_) => {}
+        Err(error) => assert_eq!(error["code"], "Unsupported"),
+    }
 }
 
 #[test]
@@ -26,16 +31,50 @@ fn test_send_notification() {
 }
 
 #[test]
-fn test_read_write_file() {
+fn test_read_write_file_outside_workspace() {
     let mut app = Builder::new().build();
     let path = "/tmp/pulsedev_test_file.txt";
     let write_result = app.command(
         "write_file",
         Some(serde_json::json!({"path": path, "contents": "test content"})),
     );
-    assert!(write_result.is_ok());
+    assert!(write_result.is_err());
     let read_result = app.command("read_file", Some(serde_json::json!({"path": path})));
-    assert!(read_result.is_ok());
-    let content: String = serde_json::from_value(read_result.unwrap()).unwrap();
-    assert_eq!(content, "test content");
+    assert!(read_result.is_err());
+}
+
+#[test]
+fn test_list_workspaces() {
+    let mut app = Builder::new().build();
+    let result = app.command("list_workspaces", None::<()>);
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_notification_inbox() {
+    let mut app = Builder::new().build();
+    let send_result = app.command(
+        "send_notification",
+        Some(serde_json::json!({"title": "Inbox", "body": "Recorded"})),
+    );
+    assert!(send_result.is_ok());
+    let list_result = app.command("list_notifications", None::<()>);
+    assert!(list_result.is_ok());
+    let records: Vec<serde_json::Value> = serde_json::from_value(list_result.unwrap()).unwrap();
+    assert_eq!(records[0]["title"], "Inbox");
+    let clear_result = app.command("clear_notifications", None::<()>);
+    assert!(clear_result.is_ok());
+}
+
+#[test]
+fn test_set_shortcuts_rejects_conflicts() {
+    let mut app = Builder::new().build();
+    let result = app.command(
+        "set_shortcuts",
+        Some(serde_json::json!({"config": {
+            "toggle_focus": "CmdOrCtrl+Alt+F",
+            "mark_stuck": "alt+CommandOrControl+f"
+        }})),
+    );
+    assert!(result.is_err());
 }
@@ -7,19 +7,32 @@ import CCMDashboard from "./components/CCMDashboard";
 import TeamRoomsDashboard from "./components/TeamRoomsDashboard";
 import EnhancedLanding from "./pages/EnhancedLanding";
 import NotFound from "./pages/NotFound";
+import FocusWidget from "./pages/FocusWidget";
+import QuickCapture from "./pages/QuickCapture";
+import { useRoutePersistence } from "./hooks/use-route-persistence";
+import { useDeepLinks } from "./hooks/use-deep-links";
 
 const queryClient = new QueryClient();
 
+const RoutePersistence = () => {
+  useRoutePersistence();
+  useDeepLinks();
+  return null;
+};
+
 const App = () => (
   <QueryClientProvider client={queryClient}>
     <TooltipProvider>
       <Toaster />
       <Sonner />
       <HashRouter>
+        <RoutePersistence />
         <Routes>
           <Route path="/" element={<EnhancedLanding />} />
           <Route path="/dashboard" element={<CCMDashboard />} />
           <Route path="/teams" element={<TeamRoomsDashboard />} />
+          <Route path="/widget" element={<FocusWidget />} />
+          <Route path="/capture" element={<QuickCapture />} />
           <Route path="*" element={<NotFound />} />
         </Routes>
       </HashRouter>
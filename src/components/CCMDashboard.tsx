@@ -1,5 +1,6 @@
 import { useState, useEffect } from 'react';
 import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query';
+import { useSearchParams } from 'react-router-dom';
 import { Card, CardContent, CardHeader, CardTitle } from '@/components/ui/card';
 import { Button } from '@/components/ui/button';
 import { Input } from '@/components/ui/input';
@@ -25,12 +26,19 @@ export default function CCMDashboard() {
   const [isFlowActive, setIsFlowActive] = useState(false);
   const { toast } = useToast();
   const queryClient = useQueryClient();
+  const [searchParams] = useSearchParams();
+  const linkedSession = searchParams.get('session');
 
+  // Open the session named by a pulsedev://session deep link, otherwise
+  // start a fresh one on mount
   useEffect(() => {
-    // Generate session ID on mount
-    const newSessionId = `session_${Date.now()}`;
-    setSessionId(newSessionId);
-  }, []);
+    if (linkedSession) {
+      localStorage.setItem('ccm_session_id', linkedSession);
+      setSessionId(linkedSession);
+    } else {
+      setSessionId(`session_${Date.now()}`);
+    }
+  }, [linkedSession]);
 
   // Context Events Query
   const { data: events, refetch: refetchEvents } = useQuery({
@@ -52,8 +52,15 @@ const getOrCreateTeamId = () => {
   return teamId;
 };
 
-export default function SCRUMDashboard() {
-  const [teamId, setTeamId] = useState(getOrCreateTeamId());
+interface SCRUMDashboardProps {
+  /** Team to show instead of the locally stored one, e.g. from a deep link. */
+  teamId?: string;
+  /** Sprint whose metrics to show instead of the current sprint's. */
+  sprintId?: string;
+}
+
+export default function SCRUMDashboard({ teamId: linkedTeamId, sprintId }: SCRUMDashboardProps = {}) {
+  const [teamId, setTeamId] = useState(linkedTeamId ?? getOrCreateTeamId());
   const [newSprintName, setNewSprintName] = useState('');
   const [newSprintGoal, setNewSprintGoal] = useState('');
   const [newStoryTitle, setNewStoryTitle] = useState('');
@@ -62,6 +69,10 @@ export default function SCRUMDashboard() {
   const { toast } = useToast();
   const queryClient = useQueryClient();
 
+  useEffect(() => {
+    if (linkedTeamId) setTeamId(linkedTeamId);
+  }, [linkedTeamId]);
+
   // Fetch current sprint
   const { data: currentSprint, isLoading: sprintLoading } = useQuery({
     queryKey: ['current-sprint', teamId],
@@ -77,10 +88,11 @@ export default function SCRUMDashboard() {
   });
 
   // Fetch sprint metrics
+  const metricsSprintId = sprintId ?? currentSprint?.id;
   const { data: sprintMetrics } = useQuery({
-    queryKey: ['sprint-metrics', teamId, currentSprint?.id],
-    queryFn: () => scrumAPI.getSprintMetrics(teamId, currentSprint?.id).then(res => res.data),
-    enabled: !!teamId && !!currentSprint?.id,
+    queryKey: ['sprint-metrics', teamId, metricsSprintId],
+    queryFn: () => scrumAPI.getSprintMetrics(teamId, metricsSprintId).then(res => res.data),
+    enabled: !!teamId && !!metricsSprintId,
   });
 
   // Create sprint mutation
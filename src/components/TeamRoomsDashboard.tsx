@@ -1,5 +1,6 @@
 import { useState, useEffect } from 'react';
 import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query';
+import { useSearchParams } from 'react-router-dom';
 import { Card, CardContent, CardHeader, CardTitle } from '@/components/ui/card';
 import { Button } from '@/components/ui/button';
 import { Input } from '@/components/ui/input';
@@ -9,6 +10,7 @@ import { Tabs, TabsContent, TabsList, TabsTrigger } from '@/components/ui/tabs';
 import { Progress } from '@/components/ui/progress';
 import { Dialog, DialogContent, DialogHeader, DialogTitle, DialogTrigger } from '@/components/ui/dialog';
 import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from '@/components/ui/select';
+import SCRUMDashboard from '@/components/SCRUMDashboard';
 import { Label } from '@/components/ui/label';
 import { Switch } from '@/components/ui/switch';
 import { Separator } from '@/components/ui/separator';
@@ -202,6 +204,7 @@ const getCurrentUserId = () => {
 export default function TeamRoomsDashboard() {
     const [currentUserId] = useState(getCurrentUserId());
     const [selectedTeam, setSelectedTeam] = useState<string | null>(null);
+    const [activeTab, setActiveTab] = useState('overview');
     const [showCreateRoom, setShowCreateRoom] = useState(false);
     const [showJoinRoom, setShowJoinRoom] = useState(false);
     const [showInviteDialog, setShowInviteDialog] = useState(false);
@@ -225,6 +228,24 @@ export default function TeamRoomsDashboard() {
 
     const { toast } = useToast();
     const queryClient = useQueryClient();
+    const [searchParams] = useSearchParams();
+    const linkedSprint = searchParams.get('sprint') ?? undefined;
+
+    // Pre-fill from pulsedev://join and pulsedev://sprint deep links
+    useEffect(() => {
+        const code = searchParams.get('join');
+        if (code) {
+            setJoinCode(code);
+            setShowJoinRoom(true);
+        }
+        const team = searchParams.get('team');
+        if (team) {
+            setSelectedTeam(team);
+        }
+        if (searchParams.get('sprint')) {
+            setActiveTab('sprint');
+        }
+    }, [searchParams]);
 
     // Fetch team rooms
     const { data: teamRooms, isLoading: roomsLoading } = useQuery({
@@ -509,7 +530,7 @@ export default function TeamRoomsDashboard() {
                 {/* Main Content */}
                 <div className="col-span-9">
                     {selectedTeam ? (
-                        <Tabs defaultValue="overview" className="space-y-4">
+                        <Tabs value={activeTab} onValueChange={setActiveTab} className="space-y-4">
                             <TabsList>
                                 <TabsTrigger value="overview">Overview</TabsTrigger>
                                 <TabsTrigger value="members">Members</TabsTrigger>
@@ -517,6 +538,7 @@ export default function TeamRoomsDashboard() {
                                 <TabsTrigger value="activity">Activity</TabsTrigger>
                                 <TabsTrigger value="analytics">Analytics</TabsTrigger>
                                 <TabsTrigger value="integrations">Integrations</TabsTrigger>
+                                <TabsTrigger value="sprint">Sprint</TabsTrigger>
                             </TabsList>
 
                             <TabsContent value="overview" className="space-y-4">
@@ -897,6 +919,10 @@ export default function TeamRoomsDashboard() {
                                     </CardContent>
                                 </Card>
                             </TabsContent>
+
+                            <TabsContent value="sprint" className="space-y-4">
+                                <SCRUMDashboard teamId={selectedTeam} sprintId={linkedSprint} />
+                            </TabsContent>
                         </Tabs>
                     ) : (
                         <Card>
@@ -0,0 +1,36 @@
+import * as React from "react"
+import { useNavigate } from "react-router-dom"
+import { listen, takeDeepLink, type DeepLink } from "@/utils/tauri-native"
+
+function routeFor(link: DeepLink): string {
+  switch (link.kind) {
+    case "join":
+      return `/teams?join=${encodeURIComponent(link.code)}`
+    case "sprint": {
+      const params = new URLSearchParams({ sprint: link.sprint_id })
+      if (link.team_id) params.set("team", link.team_id)
+      return `/teams?${params}`
+    }
+    case "session":
+      return `/dashboard?session=${encodeURIComponent(link.session_id)}`
+  }
+}
+
+/** Opens the view a `pulsedev://` link points at. */
+export function useDeepLinks() {
+  const navigate = useNavigate()
+
+  React.useEffect(() => {
+    const open = (link: DeepLink | null) => {
+      if (link) navigate(routeFor(link))
+    }
+    takeDeepLink().then(open).catch(() => {})
+    const unlisten = listen<DeepLink>("deep-link", () => {
+      // Take rather than use the payload so the link is consumed once.
+      takeDeepLink().then(open).catch(() => {})
+    })
+    return () => {
+      unlisten.then((f) => f())
+    }
+  }, [navigate])
+}
@@ -0,0 +1,35 @@
+import * as React from "react"
+import { useLocation, useNavigate } from "react-router-dom"
+import { getWindowState, setLastRoute } from "@/utils/tauri-native"
+
+/**
+ * Reopens the route the window was last showing and reports every
+ * navigation to the native side. Outside Tauri both calls reject and this
+ * does nothing.
+ */
+export function useRoutePersistence() {
+  const location = useLocation()
+  const navigate = useNavigate()
+  const restored = React.useRef(false)
+
+  React.useEffect(() => {
+    getWindowState()
+      .then((state) => {
+        if (state?.route && state.route !== location.pathname) {
+          navigate(state.route, { replace: true })
+        }
+      })
+      .catch(() => {})
+      .finally(() => {
+        restored.current = true
+      })
+    // Only on first mount.
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, [])
+
+  React.useEffect(() => {
+    if (restored.current) {
+      setLastRoute(location.pathname).catch(() => {})
+    }
+  }, [location.pathname])
+}
@@ -0,0 +1,66 @@
+import { useEffect, useState } from "react";
+import { BellOff, Pin, PinOff, X, Zap } from "lucide-react";
+import {
+  closeFocusWidget,
+  getWidgetState,
+  listen,
+  pinFocusWidget,
+  type TrayState,
+  type WidgetState,
+} from "@/utils/tauri-native";
+
+const formatRemaining = (secs: number | null) =>
+  secs === null
+    ? "--:--"
+    : `${String(Math.floor(secs / 60)).padStart(2, "0")}:${String(secs % 60).padStart(2, "0")}`;
+
+/** Compact always-on-top view rendered in the `widget` window. */
+const FocusWidget = () => {
+  const [state, setState] = useState<WidgetState | null>(null);
+
+  useEffect(() => {
+    getWidgetState().then(setState).catch(() => {});
+    const unlisten = [
+      listen<WidgetState>("widget-state", (e) => setState(e.payload)),
+      listen<TrayState>("tray-state", (e) =>
+        setState((s) => (s ? { ...s, tray: e.payload } : s))
+      ),
+    ];
+    return () => {
+      unlisten.forEach((p) => p.then((f) => f()));
+    };
+  }, []);
+
+  const tray = state?.tray;
+
+  return (
+    <div
+      data-tauri-drag-region
+      className="h-screen select-none rounded-lg bg-slate-900 p-3 text-white"
+    >
+      <div data-tauri-drag-region className="flex items-center justify-between">
+        <span className="font-mono text-2xl">
+          {formatRemaining(tray?.focus_remaining_secs ?? null)}
+        </span>
+        <div className="flex items-center gap-2">
+          {tray?.dnd && <BellOff className="h-4 w-4 text-amber-400" />}
+          <button onClick={() => pinFocusWidget(!state?.pinned).then(setState).catch(() => {})}>
+            {state?.pinned ? <Pin className="h-4 w-4" /> : <PinOff className="h-4 w-4" />}
+          </button>
+          <button onClick={() => closeFocusWidget().catch(() => {})}>
+            <X className="h-4 w-4" />
+          </button>
+        </div>
+      </div>
+      <p className="mt-1 truncate text-sm text-slate-300">
+        {state?.task ?? "No task selected"}
+      </p>
+      <div className="mt-2 flex items-center gap-1 text-sm">
+        <Zap className={`h-4 w-4 ${tray?.in_flow ? "text-green-400" : "text-slate-500"}`} />
+        <span>Flow {tray?.focus_score != null ? `${tray.focus_score}%` : "–"}</span>
+      </div>
+    </div>
+  );
+};
+
+export default FocusWidget;
@@ -0,0 +1,35 @@
+import { useState } from "react";
+import { closeWindow, isCommandError, submitQuickCapture } from "@/utils/tauri-native";
+
+/** Single-line note box rendered in the `capture` window. */
+const QuickCapture = () => {
+  const [note, setNote] = useState("");
+  const [error, setError] = useState<string | null>(null);
+
+  const submit = async () => {
+    try {
+      await submitQuickCapture(note);
+    } catch (e) {
+      setError(isCommandError(e) ? e.message : String(e));
+    }
+  };
+
+  return (
+    <div data-tauri-drag-region className="h-screen rounded-lg bg-slate-900 p-3">
+      <input
+        autoFocus
+        value={note}
+        onChange={(e) => setNote(e.target.value)}
+        onKeyDown={(e) => {
+          if (e.key === "Enter") submit();
+          if (e.key === "Escape") closeWindow().catch(() => {});
+        }}
+        placeholder="What are you trying right now?"
+        className="w-full rounded bg-slate-800 px-3 py-2 text-white outline-none"
+      />
+      {error && <p className="mt-1 text-xs text-red-400">{error}</p>}
+    </div>
+  );
+};
+
+export default QuickCapture;
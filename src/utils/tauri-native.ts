@@ -4,6 +4,32 @@ const invoke = async (command: string, args?: any) => {
   throw new Error('Tauri not available in web environment');
 };
 
+// Mock Tauri event listener for web environment; returns the unlisten function
+export const listen = async <T>(event: string, _handler: (event: { payload: T }) => void) => {
+  console.log(`Tauri listen: ${event}`);
+  return () => {};
+};
+
+export type CommandErrorCode =
+  | 'NotFound'
+  | 'PermissionDenied'
+  | 'OutOfScope'
+  | 'InvalidInput'
+  | 'BackendUnavailable'
+  | 'Unsupported'
+  | 'Conflict'
+  | 'Io';
+
+/** Shape of every error rejected by a native command. */
+export interface CommandError {
+  code: CommandErrorCode;
+  message: string;
+}
+
+export function isCommandError(e: unknown): e is CommandError {
+  return typeof e === 'object' && e !== null && 'code' in e && 'message' in e;
+}
+
 export async function pickFile(): Promise<string | null> {
   return await invoke('pick_file');
 }
@@ -20,6 +46,428 @@ export async function getDndStatus(): Promise<boolean> {
   return await invoke('get_dnd_status');
 }
 
-export async function setDndStatus(enabled: boolean): Promise<boolean> {
+export async function setDndStatus(enabled: boolean): Promise<void> {
   return await invoke('set_dnd_status', { enabled });
-} 
\ No newline at end of file
+} 
+export async function grantWorkspace(): Promise<string | null> {
+  return await invoke('grant_workspace');
+}
+
+export async function listWorkspaces(): Promise<string[]> {
+  return await invoke('list_workspaces');
+}
+
+export async function revokeWorkspace(path: string): Promise<boolean> {
+  return await invoke('revoke_workspace', { path });
+}
+
+export async function startFileWatcher(): Promise<string[]> {
+  return await invoke('start_file_watcher');
+}
+
+export async function stopFileWatcher(): Promise<void> {
+  return await invoke('stop_file_watcher');
+}
+
+export interface QueueStatus {
+  depth: number;
+  consecutive_failures: number;
+  next_retry_in_secs: number;
+  last_error: string | null;
+  last_status: number | null;
+  rejected: number;
+}
+
+export async function getEventQueueStatus(): Promise<QueueStatus> {
+  return await invoke('event_queue_status');
+}
+
+export async function flushEventQueue(): Promise<QueueStatus> {
+  return await invoke('flush_event_queue');
+}
+
+export interface ApiConfig {
+  base_url: string;
+  auth_token: string | null;
+}
+
+export async function getApiConfig(): Promise<ApiConfig> {
+  return await invoke('get_api_config');
+}
+
+export async function setApiConfig(base_url: string, auth_token: string | null): Promise<void> {
+  return await invoke('set_api_config', { baseUrl: base_url, authToken: auth_token });
+}
+
+export interface EnergyScore {
+  session_id: string;
+  energy_score: unknown;
+  trends: unknown;
+}
+
+export async function getEnergyScore(hours?: number): Promise<EnergyScore> {
+  return await invoke('get_energy_score', { hours });
+}
+
+export interface XpAwarded {
+  xp_earned: number;
+  session_id: string;
+  source: string;
+}
+
+export async function awardXp(
+  source: string,
+  options?: { amount?: number; description?: string; metadata?: Record<string, unknown> }
+): Promise<XpAwarded> {
+  return await invoke('award_xp', { source, ...options });
+}
+
+export async function syncGamificationSession(): Promise<unknown> {
+  return await invoke('sync_gamification_session');
+}
+
+export async function getAchievements(): Promise<unknown> {
+  return await invoke('get_achievements');
+}
+
+export async function getLeaderboard(category?: string, period?: string): Promise<unknown> {
+  return await invoke('get_leaderboard', { category, period });
+}
+
+export async function trackActivity(activityType: string, details?: Record<string, unknown>): Promise<void> {
+  return await invoke('track_activity', { activityType, details });
+}
+
+export async function getGamificationDashboard(): Promise<unknown> {
+  return await invoke('get_gamification_dashboard');
+}
+
+export async function getDndBackend(): Promise<string | null> {
+  return await invoke('get_dnd_backend');
+}
+
+export interface FocusSession {
+  id: string;
+  reason: string | null;
+  started_at: string;
+  ends_at: string;
+  previous_dnd: boolean;
+}
+
+export async function startFocusSession(duration: number, reason?: string): Promise<FocusSession> {
+  return await invoke('start_focus_session', { duration, reason });
+}
+
+export async function cancelFocusSession(): Promise<boolean> {
+  return await invoke('cancel_focus_session');
+}
+
+export async function getFocusSession(): Promise<FocusSession | null> {
+  return await invoke('get_focus_session');
+}
+
+export interface NotificationAction {
+  id: string;
+  label: string;
+}
+
+export interface NotificationRequest {
+  title: string;
+  body: string;
+  actions?: NotificationAction[];
+  urgency?: 'low' | 'normal' | 'critical';
+  priority?: 'low' | 'normal' | 'high' | 'critical';
+  category?: string;
+  icon?: string;
+  timeout_ms?: number;
+  replaces_id?: number;
+}
+
+export type NotificationOutcome =
+  | { status: 'delivered'; id: number }
+  | { status: 'suppressed'; reason: 'focus' | 'quiet_hours' | 'rate_limited' }
+  | { status: 'muted' }
+  | { status: 'pending' }
+  | { status: 'failed'; error: string };
+
+export async function sendRichNotification(
+  notification: NotificationRequest,
+): Promise<NotificationOutcome> {
+  return await invoke('send_rich_notification', { notification });
+}
+
+export async function closeNotification(id: number): Promise<void> {
+  return await invoke('close_notification', { id });
+}
+
+export async function flushNotificationDigest(): Promise<number> {
+  return await invoke('flush_notification_digest');
+}
+
+export interface NotificationRecord {
+  id: string;
+  notification_id: number | null;
+  title: string;
+  body: string;
+  category: string | null;
+  outcome: NotificationOutcome;
+  created_at: string;
+  read: boolean;
+  action: string | null;
+  action_at: string | null;
+}
+
+export async function listNotifications(options?: {
+  unreadOnly?: boolean;
+  category?: string;
+  limit?: number;
+}): Promise<NotificationRecord[]> {
+  return await invoke('list_notifications', options ?? {});
+}
+
+export async function markRead(ids?: string[]): Promise<number> {
+  return await invoke('mark_read', { ids });
+}
+
+export async function clearNotifications(): Promise<void> {
+  return await invoke('clear_notifications');
+}
+
+export interface TrayState {
+  in_flow: boolean | null;
+  /** Whole percent, 0–100. */
+  focus_score: number | null;
+  coding_secs_today: number;
+  xp: number | null;
+  level: number | null;
+  streak: number | null;
+  dnd: boolean;
+  focus_remaining_secs: number | null;
+  tracking_paused: boolean;
+  stuck: boolean;
+  offline: boolean;
+  queue_depth: number;
+}
+
+export async function getTrayState(): Promise<TrayState> {
+  return await invoke('get_tray_state');
+}
+
+export async function setStuckState(stuck: boolean): Promise<void> {
+  return await invoke('set_stuck_state', { stuck });
+}
+
+export interface TrackingStatus {
+  paused: boolean;
+  started_at: string | null;
+  until: string | null;
+}
+
+export interface TrackingGap {
+  started_at: string;
+  ended_at: string;
+}
+
+export async function pauseTracking(duration?: number): Promise<TrackingStatus> {
+  return await invoke('pause_tracking', { duration });
+}
+
+export async function resumeTracking(): Promise<TrackingGap | null> {
+  return await invoke('resume_tracking');
+}
+
+export async function getTrackingStatus(): Promise<TrackingStatus> {
+  return await invoke('get_tracking_status');
+}
+
+export async function listTrackingGaps(): Promise<TrackingGap[]> {
+  return await invoke('list_tracking_gaps');
+}
+
+export interface WindowState {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+  monitor: string | null;
+  maximized: boolean;
+  route: string | null;
+}
+
+export async function getWindowState(): Promise<WindowState | null> {
+  return await invoke('get_window_state');
+}
+
+export async function setLastRoute(route: string): Promise<void> {
+  return await invoke('set_last_route', { route });
+}
+
+export interface WidgetState {
+  open: boolean;
+  pinned: boolean;
+  task: string | null;
+  tray: TrayState;
+}
+
+export async function openFocusWidget(): Promise<WidgetState> {
+  return await invoke('open_focus_widget');
+}
+
+export async function closeFocusWidget(): Promise<WidgetState> {
+  return await invoke('close_focus_widget');
+}
+
+export async function pinFocusWidget(pinned: boolean): Promise<WidgetState> {
+  return await invoke('pin_focus_widget', { pinned });
+}
+
+export async function setWidgetTask(task: string | null): Promise<WidgetState> {
+  return await invoke('set_widget_task', { task });
+}
+
+export async function getWidgetState(): Promise<WidgetState> {
+  return await invoke('get_widget_state');
+}
+
+export type ShortcutAction = 'toggle_focus' | 'mark_stuck' | 'quick_capture' | 'toggle_window';
+
+export type ShortcutConfig = Partial<Record<ShortcutAction, string>>;
+
+export async function getShortcuts(): Promise<ShortcutConfig> {
+  return await invoke('get_shortcuts');
+}
+
+export async function setShortcuts(config: ShortcutConfig): Promise<void> {
+  return await invoke('set_shortcuts', { config });
+}
+
+/** Closes the window the call is made from. */
+export async function closeWindow(): Promise<void> {
+  return await invoke('close_window');
+}
+
+export async function openQuickCapture(): Promise<void> {
+  return await invoke('open_quick_capture_window');
+}
+
+/** Records a short note as a `note_captured` context event and closes the capture window. */
+export async function submitQuickCapture(note: string): Promise<unknown> {
+  return await invoke('submit_quick_capture', { note });
+}
+
+/** Payload of the `second-instance` event sent when PulseDev+ is launched again. */
+export interface InstanceArgs {
+  args: string[];
+  cwd: string | null;
+}
+
+export type DeepLink =
+  | { kind: 'join'; code: string }
+  | { kind: 'sprint'; sprint_id: string; team_id: string | null }
+  | { kind: 'session'; session_id: string };
+
+/** Returns the `pulsedev://` link that launched or focused the app, if not yet handled. */
+export async function takeDeepLink(): Promise<DeepLink | null> {
+  return await invoke('take_deep_link');
+}
+
+export async function parseLink(url: string): Promise<DeepLink> {
+  return await invoke('parse_link', { url });
+}
+
+/** (Re)starts git watching over the granted workspaces; returns the repositories watched. */
+export async function startGitWatcher(): Promise<string[]> {
+  return await invoke('start_git_watcher');
+}
+
+export async function stopGitWatcher(): Promise<void> {
+  return await invoke('stop_git_watcher');
+}
+
+export interface CommitSuggestion {
+  session_id: string;
+  message: string;
+  files: string[];
+  auto_stage: boolean;
+  confidence: number;
+}
+
+export interface FileChange {
+  path: string;
+  status: 'added' | 'modified' | 'deleted' | 'renamed' | 'typechange';
+  insertions: number;
+  deletions: number;
+}
+
+export interface CommitPlan {
+  repo: string;
+  branch: string;
+  message: string;
+  files: FileChange[];
+  unchanged: string[];
+  other_staged: string[];
+  /** Signing or hooks the commit would skip; non-empty means it is refused. */
+  bypassed: string[];
+}
+
+export interface AutoCommit {
+  repo: string;
+  branch: string;
+  hash: string;
+  parent: string;
+  message: string;
+  files: string[];
+}
+
+export async function autoCommitDryRun(repo: string, suggestion: CommitSuggestion): Promise<CommitPlan> {
+  return await invoke('auto_commit_dry_run', { repo, suggestion });
+}
+
+export async function executeAutoCommit(repo: string, suggestion: CommitSuggestion): Promise<AutoCommit> {
+  return await invoke('execute_auto_commit', { repo, suggestion });
+}
+
+export async function undoAutoCommit(): Promise<AutoCommit> {
+  return await invoke('undo_auto_commit');
+}
+
+/** Drafts a conventional commit message from the staged diff, entirely on this machine. */
+export async function draftCommitMessage(repo: string): Promise<CommitSuggestion> {
+  return await invoke('draft_commit_message', { repo });
+}
+
+export interface BranchSuggestion {
+  session_id: string;
+  branch_name: string;
+  reason: string;
+  confidence: number;
+}
+
+export interface BranchContext {
+  title?: string;
+  ticket?: string;
+  notes?: string[];
+  files?: string[];
+}
+
+export interface BranchNaming {
+  template: string;
+  max_slug_words: number;
+  max_length: number;
+}
+
+export async function suggestBranchNames(repo: string, context: BranchContext): Promise<BranchSuggestion[]> {
+  return await invoke('suggest_branch_names', { repo, context });
+}
+
+export async function createBranch(repo: string, name: string, switchTo: boolean): Promise<string> {
+  return await invoke('create_branch', { repo, name, switch: switchTo });
+}
+
+export async function getBranchNaming(): Promise<BranchNaming> {
+  return await invoke('get_branch_naming');
+}
+
+export async function setBranchNaming(config: BranchNaming): Promise<void> {
+  return await invoke('set_branch_naming', { config });
+}